@@ -0,0 +1,159 @@
+//! Measures the solver by playing every puzzle answer with its top suggestion.
+
+use std::collections::BTreeMap;
+
+use crate::constraints::Constraints;
+use crate::dictionary_set::DictionarySet;
+use crate::game::{Game, GuessResult};
+use crate::suggest::{suggest, AnswerPool, Ranking, SuggestError};
+use crate::word_list::WordList;
+use rayon::prelude::*;
+
+#[derive(Clone, Debug, Default)]
+/// How a strategy did over a set of answers.
+pub struct BenchReport {
+    /// Number of games won with each guess count.
+    pub histogram: BTreeMap<usize, usize>,
+    /// Answers the strategy ran out of guesses on.
+    pub failures: Vec<&'static str>,
+}
+
+impl BenchReport {
+    /// Number of games played, won or lost.
+    pub fn games(&self) -> usize {
+        self.histogram.values().sum::<usize>() + self.failures.len()
+    }
+
+    /// Average guesses over the games that were won.
+    pub fn mean(&self) -> f64 {
+        let (games, guesses) = self
+            .histogram
+            .iter()
+            .fold((0, 0), |(games, guesses), (g, count)| {
+                (games + count, guesses + g * count)
+            });
+        if games == 0 {
+            0.0
+        } else {
+            guesses as f64 / games as f64
+        }
+    }
+}
+
+/// Plays a game for every answer, always taking the top suggestion. Every game starts from the
+/// same position so the opening guess is computed once (unless given) and shared.
+pub fn bench(
+    answers: Vec<&'static str>,
+    opening: Option<&'static str>,
+    easy: bool,
+    ranking: Ranking,
+    pool: AnswerPool,
+    max_guesses: usize,
+) -> Result<BenchReport, SuggestError> {
+    let opening = match opening {
+        Some(word) => word,
+        None => top_suggestion(WordList::new(), easy, ranking, pool)?,
+    };
+
+    let index = DictionarySet::shared();
+    let results = answers
+        .par_iter()
+        .map(|answer| {
+            solve(answer, opening, index, easy, ranking, pool, max_guesses)
+                .map(|guesses| (*answer, guesses))
+        })
+        .collect::<Result<Vec<(&'static str, Option<usize>)>, SuggestError>>()?;
+
+    Ok(results
+        .into_iter()
+        .fold(BenchReport::default(), |mut report, (answer, guesses)| {
+            match guesses {
+                Some(g) => *report.histogram.entry(g).or_default() += 1,
+                None => report.failures.push(answer),
+            }
+            report
+        }))
+}
+
+/// Returns the number of guesses it took to win, or `None` if the game was lost. The words still in
+/// play are looked up in `index`, which holds every guess.
+fn solve(
+    answer: &str,
+    opening: &'static str,
+    index: &DictionarySet,
+    easy: bool,
+    ranking: Ranking,
+    pool: AnswerPool,
+    max_guesses: usize,
+) -> Result<Option<usize>, SuggestError> {
+    let mut game = Game::for_word(answer).set_max_guesses(max_guesses);
+    if !easy {
+        game = game.set_hard_mode();
+    }
+
+    let mut known = Constraints::new(answer.len());
+    let mut guess = opening;
+    loop {
+        let result = game.check(guess);
+        match result.result {
+            GuessResult::Win => return Ok(Some(result.guesses)),
+            GuessResult::Incorrect => {
+                known = known.apply(&result.letters);
+                guess = top_suggestion(known.filter_set(index), easy, ranking, pool)?;
+            }
+            GuessResult::Lose | GuessResult::Invalid(_) | GuessResult::Unknown(_) => {
+                return Ok(None)
+            }
+        }
+    }
+}
+
+fn top_suggestion(
+    word_list: WordList,
+    easy: bool,
+    ranking: Ranking,
+    pool: AnswerPool,
+) -> Result<&'static str, SuggestError> {
+    let suggestions = suggest(word_list, easy, ranking, pool)?;
+    suggestions
+        .first()
+        .map(|s| s.word)
+        .ok_or(SuggestError::NoWordsLeft)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bench_counts_wins_and_failures() {
+        let answers = vec!["trace", "crane", "slate"];
+        let report = bench(
+            answers.clone(),
+            Some("trace"),
+            false,
+            Ranking::Entropy,
+            AnswerPool::Contest,
+            6,
+        )
+        .unwrap();
+        assert_eq!(report.games(), 3);
+        assert!(report.failures.is_empty());
+        assert_eq!(report.histogram.get(&1), Some(&1));
+        assert!(report.mean() > 1.0);
+
+        // With one guess only the opening can win.
+        let report = bench(
+            answers,
+            Some("trace"),
+            false,
+            Ranking::Entropy,
+            AnswerPool::Contest,
+            1,
+        )
+        .unwrap();
+        assert_eq!(report.histogram, BTreeMap::from([(1, 1)]));
+        assert_eq!(report.failures.len(), 2);
+        assert_eq!(report.mean(), 1.0);
+    }
+}
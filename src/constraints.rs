@@ -0,0 +1,244 @@
+//! What the feedback so far says about the secret word, and the words that still fit it.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter::FromIterator,
+};
+
+use crate::dictionary_set::DictionarySet;
+use crate::game::{HardModeError, LetterResult};
+use crate::word_list::WordList;
+use serde::{Deserialize, Serialize};
+
+/// Everything that is known about the secret word after one or more guesses: which letters may
+/// still appear at each position, and how many copies of each letter the word holds.
+///
+/// A letter marked green or yellow sets a lower bound on its count; if the same guess also marks a
+/// copy of that letter grey, the count is known exactly. A grey letter that was not found anywhere
+/// else in the guess therefore has a maximum of zero.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Constraints {
+    allowed: Vec<BTreeSet<char>>,
+    exact: Vec<Option<char>>,
+    min_counts: BTreeMap<char, usize>,
+    max_counts: BTreeMap<char, usize>,
+}
+
+impl Constraints {
+    /// Nothing known yet about a word of `length` letters.
+    pub fn new(length: usize) -> Self {
+        Constraints {
+            allowed: vec![('a'..='z').collect(); length],
+            exact: vec![None; length],
+            min_counts: BTreeMap::new(),
+            max_counts: BTreeMap::new(),
+        }
+    }
+
+    /// Adds what the feedback for one guess reveals.
+    pub fn apply(mut self, letters: &[LetterResult]) -> Self {
+        let mut found = BTreeMap::<char, usize>::new();
+        let mut missed = BTreeSet::<char>::new();
+
+        for (i, result) in letters.iter().enumerate() {
+            match result {
+                LetterResult::Exact(c) => {
+                    self.allowed[i] = BTreeSet::from([*c]);
+                    self.exact[i] = Some(*c);
+                    *found.entry(*c).or_default() += 1;
+                }
+                LetterResult::Contains(c) => {
+                    self.allowed[i].remove(c);
+                    *found.entry(*c).or_default() += 1;
+                }
+                LetterResult::NotFound(c) => {
+                    self.allowed[i].remove(c);
+                    missed.insert(*c);
+                }
+            }
+        }
+
+        for (c, count) in found.iter() {
+            let min = self.min_counts.entry(*c).or_default();
+            *min = (*min).max(*count);
+        }
+
+        for c in missed {
+            self.max_counts
+                .insert(c, found.get(&c).copied().unwrap_or(0));
+        }
+
+        self
+    }
+
+    /// Whether `word` could still be the secret word.
+    pub fn matches(&self, word: &str) -> bool {
+        if word.chars().count() != self.allowed.len() {
+            return false;
+        }
+
+        let mut counts = BTreeMap::<char, usize>::new();
+        for (i, c) in word.chars().enumerate() {
+            if !self.allowed.get(i).is_some_and(|a| a.contains(&c)) {
+                return false;
+            }
+            *counts.entry(c).or_default() += 1;
+        }
+
+        let count_of = |c: &char| counts.get(c).copied().unwrap_or(0);
+        self.min_counts.iter().all(|(c, min)| count_of(c) >= *min)
+            && self.max_counts.iter().all(|(c, max)| count_of(c) <= *max)
+    }
+
+    /// Hard mode only enforces what has been revealed: green letters must stay where they were
+    /// found and every revealed letter must be used at least as many times as it was revealed.
+    /// Letters known to be absent or misplaced may still be guessed.
+    pub fn check_hard_mode(&self, guess: &str) -> Result<(), HardModeError> {
+        let chars = Vec::from_iter(guess.chars());
+        for (position, exact) in self.exact.iter().enumerate() {
+            if let Some(letter) = exact {
+                if chars.get(position) != Some(letter) {
+                    return Err(HardModeError::Position {
+                        position,
+                        letter: *letter,
+                    });
+                }
+            }
+        }
+
+        for (letter, count) in self.min_counts.iter() {
+            if chars.iter().filter(|c| *c == letter).count() < *count {
+                return Err(HardModeError::Missing {
+                    letter: *letter,
+                    count: *count,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The words in `word_list` that could still be the secret word. Words from the guess list are
+    /// narrowed down in the shared index with `filter_set`; only any others are checked one by
+    /// one.
+    pub fn filter(&self, word_list: &WordList) -> WordList {
+        let index = DictionarySet::shared();
+        let mut words = self.filter_set(index).get();
+        words.retain(|w| word_list.contains(w));
+        words.extend(
+            word_list
+                .get()
+                .into_iter()
+                .filter(|w| !index.contains(w) && self.matches(w)),
+        );
+        WordList::from(words)
+    }
+
+    /// Same as `filter` over the words in `set`, narrowing the set down by position and presence
+    /// with bitset operations first. Only the words left after that are checked one by one for
+    /// letter counts.
+    pub fn filter_set(&self, set: &DictionarySet) -> WordList {
+        let mut bits = set.all();
+        for (i, (allowed, exact)) in self.allowed.iter().zip(self.exact.iter()).enumerate() {
+            match exact {
+                Some(c) => bits = bits.and(set.at_position(i, *c)),
+                None => {
+                    for c in ('a'..='z').filter(|c| !allowed.contains(c)) {
+                        bits = bits.and_not(set.at_position(i, c));
+                    }
+                }
+            }
+        }
+        for (c, _) in self.min_counts.iter().filter(|(_, min)| **min > 0) {
+            bits = bits.and(set.containing(*c));
+        }
+        for (c, _) in self.max_counts.iter().filter(|(_, max)| **max == 0) {
+            bits = bits.and_not(set.containing(*c));
+        }
+
+        WordList::from(
+            set.words(&bits)
+                .into_iter()
+                .filter(|w| self.matches(w))
+                .collect::<Vec<&'static str>>(),
+        )
+    }
+}
+
+/// The words in `word_list` that could have produced the feedback in `letters`.
+pub fn eliminate_words(word_list: WordList, letters: Vec<LetterResult>) -> WordList {
+    Constraints::new(letters.len())
+        .apply(&letters)
+        .filter(&word_list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{compare, pattern};
+
+    #[test]
+    fn constraints_for_the_shortest_and_longest_words() {
+        let known = after(&["toot"], "tote");
+        let words = WordList::from(vec!["tote", "toot", "toto", "teto"]);
+        assert_eq!(known.filter(&words).get(), vec!["tote"]);
+
+        let known = after(&["programmers"], "programming");
+        assert!(known.matches("programming"));
+        assert!(!known.matches("programmers"));
+        assert!(known.check_hard_mode("xrogrammers").is_err());
+        assert!(known.check_hard_mode("programmxyz").is_ok());
+    }
+
+    fn after(guesses: &[&str], answer: &str) -> Constraints {
+        guesses
+            .iter()
+            .fold(Constraints::new(answer.len()), |constraints, guess| {
+                constraints.apply(&compare(guess, answer))
+            })
+    }
+
+    #[test]
+    fn grey_copy_of_a_found_letter_caps_its_count() {
+        // lolly against llama: the first l is green, the second yellow and the third grey.
+        let constraints = after(&["lolly"], "llama");
+        assert_eq!(constraints.min_counts.get(&'l'), Some(&2));
+        assert_eq!(constraints.max_counts.get(&'l'), Some(&2));
+        assert!(constraints.matches("llama"));
+        assert!(!constraints.matches("lemma"));
+    }
+
+    #[test]
+    fn exact_counts_reject_more_and_fewer_copies() {
+        // geese against sheep: two e's are found and the third is grey, so sheep has exactly
+        // two. The made-up words below only differ in how many e's they hold.
+        let constraints = after(&["geese"], "sheep");
+        assert!(constraints.matches("sheep"));
+        assert!(constraints.matches("esexs"));
+        assert!(!constraints.matches("esees"));
+        assert!(!constraints.matches("xsexs"));
+    }
+
+    #[test]
+    fn constraints_agree_with_pattern() {
+        let words = WordList::contest().get();
+        let set = DictionarySet::from_word_list(&WordList::contest());
+        for (guess, answer) in words.iter().step_by(97).zip(words.iter().step_by(89)) {
+            let constraints = after(&[guess], answer);
+            let mut expected = words
+                .iter()
+                .filter(|w| pattern(guess, w) == pattern(guess, answer))
+                .copied()
+                .collect::<Vec<&str>>();
+            expected.sort_unstable();
+
+            let mut filtered = constraints.filter(&WordList::contest()).get();
+            filtered.sort_unstable();
+            assert_eq!(filtered, expected, "{} against {}", guess, answer);
+
+            let mut from_set = constraints.filter_set(&set).get();
+            from_set.sort_unstable();
+            assert_eq!(from_set, expected, "{} against {}", guess, answer);
+        }
+    }
+}
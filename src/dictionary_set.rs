@@ -1,45 +1,198 @@
-use std::collections::{HashMap, HashSet};
+//! Bitset index over a word list for narrowing it down letter by letter.
+
+use std::sync::OnceLock;
 
 use crate::word_list::WordList;
 
+static SHARED: OnceLock<DictionarySet> = OnceLock::new();
+
+/// A subset of the words in a `DictionarySet`, one bit per word.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordBits {
+    blocks: Vec<u64>,
+}
+
+impl WordBits {
+    fn empty(len: usize) -> Self {
+        WordBits {
+            blocks: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    fn full(len: usize) -> Self {
+        let mut bits = WordBits {
+            blocks: vec![u64::MAX; len.div_ceil(64)],
+        };
+        if let (Some(last), tail @ 1..) = (bits.blocks.last_mut(), len % 64) {
+            *last = (1 << tail) - 1;
+        }
+        bits
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.blocks[index / 64] |= 1 << (index % 64);
+    }
+
+    /// Keeps the words also in `other`.
+    pub fn and(mut self, other: &WordBits) -> Self {
+        for (block, other) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+            *block &= other;
+        }
+        self
+    }
+
+    /// Drops the words in `other`.
+    pub fn and_not(mut self, other: &WordBits) -> Self {
+        for (block, other) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+            *block &= !other;
+        }
+        self
+    }
+
+    /// Number of words in the subset.
+    pub fn count(&self) -> usize {
+        self.blocks.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Number of words in both subsets, without building the intersection.
+    pub fn count_and(&self, other: &WordBits) -> usize {
+        self.blocks
+            .iter()
+            .zip(other.blocks.iter())
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    /// The index of each word in the subset, in order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().enumerate().flat_map(|(i, block)| {
+            let mut block = *block;
+            std::iter::from_fn(move || {
+                if block == 0 {
+                    return None;
+                }
+                let bit = block.trailing_zeros() as usize;
+                block &= block - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+}
+
+/// An index over a word list. Every word gets a dense index, and for each letter there is a
+/// bitset of the words holding it at each position and of the words holding it anywhere, so
+/// narrowing the list down is a series of ANDs and counting matches is a popcount.
 #[derive(Clone, Debug)]
 pub struct DictionarySet {
-    position_maps: [HashMap<char, HashSet<&'static str>>; 5],
-    contains_map: HashMap<char, HashSet<&'static str>>,
+    words: Vec<&'static str>,
+    position_maps: Vec<Vec<WordBits>>,
+    contains_map: Vec<WordBits>,
+    none: WordBits,
 }
 
 impl DictionarySet {
-    pub fn new() -> Self {
-        let mut prototype = HashMap::<char, HashSet<&'static str>>::new();
-        for c in 'a'..='z' {
-            prototype.insert(c, Default::default());
+    /// The index of every accepted guess (`WordList::new()`), built on first use.
+    pub fn shared() -> &'static DictionarySet {
+        SHARED.get_or_init(|| DictionarySet::from_word_list(&WordList::new()))
+    }
+
+    /// Indexes the words of `word_list`.
+    pub fn from_word_list(word_list: &WordList) -> Self {
+        let mut words = word_list.get();
+        words.sort_unstable();
+        let length = words.iter().map(|w| w.len()).max().unwrap_or(0);
+
+        let mut set = DictionarySet {
+            position_maps: vec![vec![WordBits::empty(words.len()); 26]; length],
+            contains_map: vec![WordBits::empty(words.len()); 26],
+            none: WordBits::empty(words.len()),
+            words,
+        };
+        for (index, word) in set.words.iter().enumerate() {
+            for (i, c) in word.chars().enumerate() {
+                if let Some(letter) = letter_index(c) {
+                    set.position_maps[i][letter].insert(index);
+                    set.contains_map[letter].insert(index);
+                }
+            }
         }
+        set
+    }
+
+    /// Every word in the index.
+    pub fn all(&self) -> WordBits {
+        WordBits::full(self.words.len())
+    }
 
-        DictionarySet {
-            position_maps: [
-                prototype.clone(),
-                prototype.clone(),
-                prototype.clone(),
-                prototype.clone(),
-                prototype.clone(),
-            ],
-            contains_map: prototype.clone(),
+    /// Words with `c` at position `index`.
+    pub fn at_position(&self, index: usize, c: char) -> &WordBits {
+        match (self.position_maps.get(index), letter_index(c)) {
+            (Some(map), Some(letter)) => &map[letter],
+            _ => &self.none,
         }
     }
 
-    pub fn from_word_list(word_list: &WordList) -> Self {
-        let initial = DictionarySet::new();
-        let list = word_list.get();
-        list.iter().fold(initial, |mut set, item| {
-            for (i, c) in item.chars().enumerate() {
-                set.position_maps[i].get_mut(&c).unwrap().insert(item);
-                set.contains_map.get_mut(&c).unwrap().insert(item);
-            }
-            set
-        })
+    /// Words with `c` anywhere.
+    pub fn containing(&self, c: char) -> &WordBits {
+        letter_index(c).map_or(&self.none, |letter| &self.contains_map[letter])
+    }
+
+    /// Number of words with `c` at position `index`.
+    pub fn count_at_position(&self, index: usize, c: char) -> usize {
+        self.at_position(index, c).count()
+    }
+
+    /// Whether `word` is in the index.
+    pub fn contains(&self, word: &str) -> bool {
+        self.index_of(word).is_some()
     }
 
-    pub fn list_for_position(&self, index: usize) -> HashMap<char, HashSet<&'static str>> {
-        self.position_maps[index].clone()
+    /// The subset holding those of `words` that are in the index.
+    pub fn bits_of<'a>(&self, words: impl IntoIterator<Item = &'a str>) -> WordBits {
+        let mut bits = WordBits::empty(self.words.len());
+        for index in words.into_iter().filter_map(|w| self.index_of(w)) {
+            bits.insert(index);
+        }
+        bits
+    }
+
+    fn index_of(&self, word: &str) -> Option<usize> {
+        self.words.binary_search_by(|w| (*w).cmp(word)).ok()
+    }
+
+    /// The words in `bits`, in alphabetical order.
+    pub fn words(&self, bits: &WordBits) -> Vec<&'static str> {
+        bits.iter().map(|index| self.words[index]).collect()
+    }
+}
+
+fn letter_index(c: char) -> Option<usize> {
+    c.is_ascii_lowercase().then(|| (c as u8 - b'a') as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitsets_match_the_words() {
+        let words: Vec<&'static str> = (0..130)
+            .map(|i| if i % 3 == 0 { "crane" } else { "slate" })
+            .enumerate()
+            .map(|(i, w)| &*Box::leak(format!("{}{}", w, i).into_boxed_str()))
+            .collect();
+        let set = DictionarySet::from_word_list(&WordList::from(words.clone()));
+        assert_eq!(set.all().count(), 130);
+        assert_eq!(set.count_at_position(0, 'c'), 44);
+        assert_eq!(set.containing('s').count(), 86);
+
+        let crane = set.words(&set.all().and(set.at_position(1, 'r')));
+        assert!(crane.iter().all(|w| w.starts_with("crane")));
+        assert_eq!(set.all().and_not(set.containing('a')).count(), 0);
+
+        let some = set.bits_of(["crane0", "slate1", "gecko"]);
+        assert_eq!(some.count(), 2);
+        assert_eq!(set.containing('c').count_and(&some), 1);
+        assert!(set.contains("slate128") && !set.contains("gecko"));
     }
 }
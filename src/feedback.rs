@@ -0,0 +1,239 @@
+//! Reading and writing rows of feedback marks, in letters or as share-grid emoji.
+
+use std::fmt;
+
+use crate::game::LetterResult;
+use serde::Serialize;
+
+/// The color shown for a single letter, without the letter itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mark {
+    /// The right letter in the right place.
+    Green,
+    /// A letter of the word, in the wrong place.
+    Yellow,
+    /// Not in the word, or no more copies of it.
+    Grey,
+}
+
+/// How a row of marks is written out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    /// `G`, `Y` and `.`
+    Letters,
+    /// 🟩, 🟨 and ⬛, as in the share grid
+    Emoji,
+}
+
+/// Why a row of marks could not be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeedbackError {
+    /// The row held no marks.
+    Empty,
+    /// The row had no `:` between the guess and its marks.
+    MissingSeparator(String),
+    /// A character that is not a mark.
+    InvalidMark {
+        /// The character found.
+        mark: char,
+        /// Its position among the marks, from 0.
+        position: usize,
+    },
+    /// The guess and the marks differ in length.
+    LengthMismatch {
+        /// The guess.
+        guess: String,
+        /// Number of marks given.
+        marks: usize,
+    },
+}
+
+impl fmt::Display for FeedbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedbackError::Empty => write!(f, "no marks given"),
+            FeedbackError::MissingSeparator(row) => {
+                write!(f, "'{}' should look like guess:marks", row)
+            }
+            FeedbackError::InvalidMark { mark, position } => write!(
+                f,
+                "'{}' at position {} is not a mark; use G, Y or . (or 🟩, 🟨 or ⬛)",
+                mark,
+                position + 1
+            ),
+            FeedbackError::LengthMismatch { guess, marks } => write!(
+                f,
+                "'{}' has {} letters but {} marks were given",
+                guess,
+                guess.chars().count(),
+                marks
+            ),
+        }
+    }
+}
+
+impl Mark {
+    /// Reads a mark written as a letter, `.` or a colored square (the high-contrast ones too).
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'g' | 'G' | '🟩' | '🟧' => Some(Mark::Green),
+            'y' | 'Y' | '🟨' | '🟦' => Some(Mark::Yellow),
+            '.' | '⬛' | '⬜' => Some(Mark::Grey),
+            _ => None,
+        }
+    }
+
+    /// Writes the mark in `notation`.
+    pub fn to_char(self, notation: Notation) -> char {
+        match (notation, self) {
+            (Notation::Letters, Mark::Green) => 'G',
+            (Notation::Letters, Mark::Yellow) => 'Y',
+            (Notation::Letters, Mark::Grey) => '.',
+            (Notation::Emoji, Mark::Green) => '🟩',
+            (Notation::Emoji, Mark::Yellow) => '🟨',
+            (Notation::Emoji, Mark::Grey) => '⬛',
+        }
+    }
+}
+
+impl From<&LetterResult> for Mark {
+    fn from(letter: &LetterResult) -> Self {
+        match letter {
+            LetterResult::Exact(_) => Mark::Green,
+            LetterResult::Contains(_) => Mark::Yellow,
+            LetterResult::NotFound(_) => Mark::Grey,
+        }
+    }
+}
+
+/// Parses a row of marks in either notation. Whitespace is ignored and letters are not case
+/// sensitive, so `gy..g`, `GY..G` and `🟩🟨⬛⬛🟩` are the same row.
+pub fn parse_marks(marks: &str) -> Result<Vec<Mark>, FeedbackError> {
+    let marks = marks
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .enumerate()
+        .map(|(position, mark)| {
+            Mark::from_char(mark).ok_or(FeedbackError::InvalidMark { mark, position })
+        })
+        .collect::<Result<Vec<Mark>, FeedbackError>>()?;
+
+    if marks.is_empty() {
+        return Err(FeedbackError::Empty);
+    }
+    Ok(marks)
+}
+
+/// Pairs each letter of `guess` with its mark.
+pub fn apply_marks(guess: &str, marks: &[Mark]) -> Result<Vec<LetterResult>, FeedbackError> {
+    let guess = guess.trim().to_ascii_lowercase();
+    if guess.chars().count() != marks.len() {
+        return Err(FeedbackError::LengthMismatch {
+            guess,
+            marks: marks.len(),
+        });
+    }
+
+    Ok(guess
+        .chars()
+        .zip(marks.iter())
+        .map(|(c, mark)| match mark {
+            Mark::Green => LetterResult::Exact(c),
+            Mark::Yellow => LetterResult::Contains(c),
+            Mark::Grey => LetterResult::NotFound(c),
+        })
+        .collect())
+}
+
+/// Parses a guess and the colors seen for it, written `guess:marks` (e.g. `crane:gy..g`).
+pub fn parse_row(row: &str) -> Result<Vec<LetterResult>, FeedbackError> {
+    let (guess, marks) = row
+        .split_once(':')
+        .ok_or_else(|| FeedbackError::MissingSeparator(row.to_string()))?;
+    apply_marks(guess, &parse_marks(marks)?)
+}
+
+/// Encodes marks the same way as `game::pattern`, so a row from a share grid can be compared with
+/// computed feedback.
+pub fn pattern_of(marks: &[Mark]) -> u32 {
+    marks.iter().fold(0, |acc, mark| {
+        acc * 3
+            + match mark {
+                Mark::Grey => 0,
+                Mark::Yellow => 1,
+                Mark::Green => 2,
+            }
+    })
+}
+
+/// Writes the marks of a row, without the guess.
+pub fn format_marks(letters: &[LetterResult], notation: Notation) -> String {
+    letters
+        .iter()
+        .map(|letter| Mark::from(letter).to_char(notation))
+        .collect()
+}
+
+/// Writes a row in the form accepted by `parse_row`.
+pub fn format_row(letters: &[LetterResult], notation: Notation) -> String {
+    let guess: String = letters.iter().map(|letter| letter.to_char()).collect();
+    format!("{}:{}", guess, format_marks(letters, notation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{compare, pattern};
+
+    #[test]
+    fn round_trip() {
+        let letters = compare("eerie", "geese");
+        let row = format_row(&letters, Notation::Letters);
+        assert_eq!(row, "eerie:YG..G");
+        assert_eq!(
+            format_row(&parse_row(&row).unwrap(), Notation::Letters),
+            row
+        );
+        assert_eq!(format_marks(&letters, Notation::Emoji), "🟨🟩⬛⬛🟩");
+        assert_eq!(
+            pattern_of(&parse_marks("🟨🟩⬛⬛🟩").unwrap()),
+            pattern("eerie", "geese")
+        );
+    }
+
+    #[test]
+    fn notations_are_interchangeable() {
+        assert_eq!(
+            parse_marks("gy..g").unwrap(),
+            parse_marks("🟩🟨⬛⬜🟩").unwrap()
+        );
+        assert_eq!(
+            parse_marks("G Y . . G").unwrap(),
+            parse_marks("🟧🟦⬛⬛🟧").unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            parse_row("crane"),
+            Err(FeedbackError::MissingSeparator("crane".to_string()))
+        );
+        assert_eq!(
+            parse_row("crane:gy.xg"),
+            Err(FeedbackError::InvalidMark {
+                mark: 'x',
+                position: 3
+            })
+        );
+        assert_eq!(
+            parse_row("crane:gy.g"),
+            Err(FeedbackError::LengthMismatch {
+                guess: "crane".to_string(),
+                marks: 4
+            })
+        );
+        assert_eq!(parse_marks(" "), Err(FeedbackError::Empty));
+    }
+}
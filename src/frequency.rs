@@ -0,0 +1,72 @@
+//! How common each word is, used to weight likely answers and break ties.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+static BNC: OnceLock<Frequency> = OnceLock::new();
+
+/// Word counts from the British National Corpus frequency list bundled with the repository. Each
+/// line is `count word part-of-speech files`; a word appears once per part of speech so the counts
+/// are summed.
+#[derive(Clone, Debug)]
+pub struct Frequency {
+    counts: HashMap<&'static str, u64>,
+}
+
+impl Frequency {
+    /// The bundled counts, parsed on first use.
+    pub fn bnc() -> &'static Frequency {
+        BNC.get_or_init(|| Frequency::parse(include_str!("../all.num.o5")))
+    }
+
+    /// Reads a list in the BNC format. Lines that don't parse and words that aren't all lowercase
+    /// letters are skipped.
+    pub fn parse(contents: &'static str) -> Self {
+        let mut counts = HashMap::<&'static str, u64>::new();
+        for line in contents.lines() {
+            let mut fields = line.split_whitespace();
+            if let (Some(Ok(count)), Some(word)) =
+                (fields.next().map(str::parse::<u64>), fields.next())
+            {
+                if word.chars().all(|c| c.is_ascii_lowercase()) {
+                    *counts.entry(word).or_default() += count;
+                }
+            }
+        }
+        Frequency { counts }
+    }
+
+    /// Occurrences of `word`; 0 if it is not in the corpus.
+    pub fn count(&self, word: &str) -> u64 {
+        self.counts.get(word).copied().unwrap_or(0)
+    }
+
+    /// Relative likelihood of `word` being an answer. Counts span several orders of magnitude, so
+    /// the weight grows with the log of the count; words missing from the corpus still get 1.
+    pub fn weight(&self, word: &str) -> f64 {
+        1.0 + (self.count(word) as f64).ln_1p()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_are_summed_over_parts_of_speech() {
+        let frequency = Frequency::parse(concat!(
+            "100106029 !!WHOLE_CORPUS !!ANY 4124\n",
+            "40 crane nn1 30\n",
+            "9 crane vvi 7\n",
+            "12 Crane np0 10\n",
+            "3 o'er prp 2\n",
+            "not-a-count slate nn1 1\n",
+        ));
+        assert_eq!(frequency.count("crane"), 49);
+        assert_eq!(frequency.count("o'er"), 0);
+        assert_eq!(frequency.count("slate"), 0);
+        assert_eq!(frequency.counts.len(), 1);
+
+        assert_eq!(frequency.weight("slate"), 1.0);
+        assert!((frequency.weight("crane") - (1.0 + 50_f64.ln())).abs() < 1e-12);
+    }
+}
@@ -1,25 +1,46 @@
+//! The rules of the game: scoring a guess, hard mode and winning or losing.
+
 use chrono::prelude::*;
-use std::{collections::BTreeSet, convert::TryInto, iter::FromIterator};
+use itertools::Itertools;
+use std::{cmp::Reverse, collections::BTreeMap, convert::TryInto, fmt, iter::FromIterator};
 
+use crate::constraints::{eliminate_words, Constraints};
+use crate::feedback::Mark;
 use crate::word_list::WordList;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+/// One puzzle: the secret word, the guesses made so far and the rules they are checked against.
+///
+/// Games serialize everything but the word lists, which are rebuilt from the running
+/// configuration.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Game {
     guesses: Vec<CheckData>,
     word: String,
     day: Option<usize>,
     hard: bool,
-    revealed: BTreeSet<char>,
+    /// `None` for no limit.
+    max_guesses: Option<usize>,
+    revealed: Constraints,
+    #[serde(skip, default = "WordList::new")]
+    word_list: WordList,
+    #[serde(skip)]
+    adversary: Option<WordList>,
 }
 
-#[derive(Clone, Debug)]
+/// The feedback for one letter of a guess.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LetterResult {
+    /// Green: the letter is in this position.
     Exact(char),
+    /// Yellow: the letter is elsewhere in the word.
     Contains(char),
+    /// Grey: the word holds no more copies of the letter.
     NotFound(char),
 }
 
 impl LetterResult {
+    /// Whether the letter is green or yellow.
     pub fn is_found(&self) -> bool {
         match self {
             LetterResult::Exact(_) => true,
@@ -28,6 +49,7 @@ impl LetterResult {
         }
     }
 
+    /// The guessed letter.
     pub fn to_char(&self) -> char {
         match self {
             LetterResult::Exact(c) | LetterResult::Contains(c) | LetterResult::NotFound(c) => *c,
@@ -35,34 +57,116 @@ impl LetterResult {
     }
 }
 
-#[derive(Clone, Debug)]
+/// What a call to `Game::check` did.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum GuessResult {
+    /// The guess was the secret word.
     Win,
+    /// The guess was wrong and there are guesses left.
     Incorrect,
+    /// The guess was wrong and it was the last one.
     Lose,
-    Invalid(String),
+    /// The guess broke a hard mode rule and was not counted.
+    Invalid(HardModeError),
+    /// The guess is not in the word list and was not counted.
+    Unknown(String),
+}
+
+/// The hard mode rule a guess broke. Positions are zero-based.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HardModeError {
+    /// A green letter was not kept in place.
+    Position {
+        /// Where the letter was found.
+        position: usize,
+        /// The letter.
+        letter: char,
+    },
+    /// A letter that was found is missing, or there are too few copies of it.
+    Missing {
+        /// The letter.
+        letter: char,
+        /// Copies of it the guess must hold.
+        count: usize,
+    },
+}
+
+impl fmt::Display for HardModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HardModeError::Position { position, letter } => write!(
+                f,
+                "{} letter must be {}",
+                ordinal(position + 1),
+                letter.to_ascii_uppercase()
+            ),
+            HardModeError::Missing { letter, count: 1 } => {
+                write!(f, "Guess must contain {}", letter.to_ascii_uppercase())
+            }
+            HardModeError::Missing { letter, count } => write!(
+                f,
+                "Guess must contain {} copies of {}",
+                count,
+                letter.to_ascii_uppercase()
+            ),
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// A checked guess.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CheckData {
+    /// Feedback for each letter; all grey when the guess was not counted.
     pub letters: Vec<LetterResult>,
+    /// What the guess did to the game.
     pub result: GuessResult,
-    pub guesses: u8,
+    /// Number of guesses made including this one, or zero if it was not counted.
+    pub guesses: usize,
 }
 
 impl Game {
+    /// A game with `word` as the secret, in easy mode with six guesses. Guesses are lowercased
+    /// before they are checked, so the word is too.
     pub fn for_word(word: &str) -> Self {
         Game {
             guesses: vec![],
-            revealed: BTreeSet::new(),
-            word: word.to_string(),
+            revealed: Constraints::new(word.len()),
+            word: word.to_ascii_lowercase(),
             day: None,
             hard: false,
+            max_guesses: Some(6),
+            word_list: WordList::new(),
+            adversary: None,
+        }
+    }
+
+    /// An Absurdle-style game with no secret word. Each guess gets whichever feedback keeps the
+    /// most words from `answers` in play, so the answer is only pinned down once nothing else is
+    /// left. As in Absurdle there is no limit on the number of guesses until one is set.
+    pub fn adversarial(answers: WordList) -> Self {
+        let mut words = answers.get();
+        words.sort_unstable();
+        Game {
+            adversary: Some(answers),
+            max_guesses: None,
+            ..Game::for_word(words.first().copied().unwrap_or_default())
         }
     }
 
-    pub fn new(day: Option<usize>) -> Self {
-        let day = day.or_else(|| {
+    /// Today's puzzle number unless `day` is given.
+    pub fn puzzle_day(day: Option<usize>) -> Option<usize> {
+        day.or_else(|| {
             let wepoch = Local.ymd(2021, 6, 19);
             Local::today()
                 .signed_duration_since::<Local>(wepoch)
@@ -70,80 +174,152 @@ impl Game {
                 .abs()
                 .try_into()
                 .ok()
-        });
+        })
+    }
 
-        let word = day.and_then(WordList::get_word_for_day).unwrap();
+    /// The puzzle for `day`, or today's; `None` if the answer list has no word for that day.
+    pub fn new(day: Option<usize>) -> Option<Self> {
+        let day = Game::puzzle_day(day);
+        let word = day.and_then(WordList::get_word_for_day)?;
 
-        Game {
+        Some(Game {
             day,
             ..Game::for_word(word)
-        }
+        })
     }
 
+    /// Requires every guess to use the green and yellow letters found so far.
     pub fn set_hard_mode(self) -> Self {
         Game { hard: true, ..self }
     }
 
+    /// Replaces the list of words accepted as guesses. The secret word is always accepted.
+    pub fn set_word_list(self, word_list: WordList) -> Self {
+        Game { word_list, ..self }
+    }
+
+    /// Ends the game in a loss after `max_guesses` wrong guesses.
+    pub fn set_max_guesses(self, max_guesses: usize) -> Self {
+        Game {
+            max_guesses: Some(max_guesses),
+            ..self
+        }
+    }
+
+    /// Whether this is an Absurdle-style game (see `Game::adversarial`).
+    pub fn is_adversarial(&self) -> bool {
+        self.adversary.is_some()
+    }
+
+    /// The puzzle number, if this is a daily puzzle.
     pub fn day(&self) -> Option<usize> {
         self.day
     }
 
+    /// Whether hard mode is off.
     pub fn is_easy(&self) -> bool {
         !self.hard
     }
 
+    /// The number of guesses allowed, or `None` if there is no limit.
+    pub fn max_guesses(&self) -> Option<usize> {
+        self.max_guesses
+    }
+
+    /// What the counted guesses have revealed about the secret word.
+    pub fn revealed(&self) -> &Constraints {
+        &self.revealed
+    }
+
+    /// Number of letters in the secret word.
+    pub fn word_length(&self) -> usize {
+        self.word.len()
+    }
+
+    /// The result of the last guess, or `None` before the first one.
+    pub fn result(&self) -> Option<GuessResult> {
+        self.guesses.last().map(|g| g.result.clone())
+    }
+
+    /// The score as the share line shows it: the number of guesses taken, or `X` for a loss, out
+    /// of the limit (`∞` without one).
+    pub fn score(&self) -> String {
+        let limit = self
+            .max_guesses
+            .map_or_else(|| "∞".to_string(), |max| max.to_string());
+        match self.result() {
+            Some(GuessResult::Lose) => format!("X/{}", limit),
+            _ => format!("{}/{}", self.guesses.len(), limit),
+        }
+    }
+
+    /// Every counted guess, oldest first.
     pub fn guesses(&self) -> Vec<CheckData> {
         self.guesses.clone()
     }
 
+    /// The best mark each guessed letter has received so far, as the web game's keyboard shows
+    /// it: green beats yellow, which beats grey. Letters not guessed yet are missing.
+    pub fn letter_states(&self) -> BTreeMap<char, Mark> {
+        let rank = |mark: &Mark| match mark {
+            Mark::Grey => 0,
+            Mark::Yellow => 1,
+            Mark::Green => 2,
+        };
+        self.guesses.iter().flat_map(|g| g.letters.iter()).fold(
+            BTreeMap::new(),
+            |mut states, letter| {
+                let mark = Mark::from(letter);
+                let state = states.entry(letter.to_char()).or_insert(mark);
+                if rank(&mark) > rank(state) {
+                    *state = mark;
+                }
+                states
+            },
+        )
+    }
+
+    /// The secret word. In an adversarial game this is only settled once the guesses leave one
+    /// answer.
     pub fn word(&self) -> String {
         self.word.clone()
     }
 
+    /// Plays `guess`. Once the game is won or lost, the last guess is returned again.
     pub fn check(&mut self, guess: &str) -> CheckData {
         if let Some(last_guess) = self.guesses.last() {
-            if let GuessResult::Lose = last_guess.result {
+            if let GuessResult::Win | GuessResult::Lose = last_guess.result {
                 return last_guess.clone();
             }
         }
 
+        let guess = guess.to_ascii_lowercase();
+        if guess != self.word && !self.word_list.contains(&guess) {
+            return CheckData {
+                letters: guess.chars().map(LetterResult::NotFound).collect(),
+                result: GuessResult::Unknown(guess),
+                guesses: 0,
+            };
+        }
+
         if self.hard {
-            for revealed in self.revealed.iter() {
-                if !guess.contains(*revealed) {
-                    return CheckData {
-                        letters: guess.chars().map(|c| LetterResult::NotFound(c)).collect(),
-                        result: GuessResult::Invalid(guess.to_string()),
-                        guesses: 0,
-                    };
-                }
+            if let Err(e) = self.revealed.check_hard_mode(&guess) {
+                return CheckData {
+                    letters: guess.chars().map(LetterResult::NotFound).collect(),
+                    result: GuessResult::Invalid(e),
+                    guesses: 0,
+                };
             }
         }
 
-        let mut word_chars = Vec::from_iter(self.word.chars());
-        let mut letters: Vec<LetterResult> = vec![];
-
-        for (i, c) in guess.to_ascii_lowercase().chars().enumerate() {
-            letters.push(match word_chars.get_mut(i) {
-                Some(fc) if *fc == c => {
-                    *fc = '_';
-                    self.revealed.insert(c);
-                    LetterResult::Exact(c)
-                }
-                _ => LetterResult::NotFound(c),
-            });
+        if let Some(remaining) = self.adversary.take() {
+            self.word = hardest_word(&guess, &remaining).to_string();
+            let letters = compare(&guess, &self.word);
+            self.adversary = Some(eliminate_words(remaining, letters));
         }
 
-        word_chars.sort();
-
-        for lr in letters.iter_mut() {
-            if let LetterResult::NotFound(c) = lr {
-                if let Ok(found_at) = word_chars.binary_search(&c) {
-                    word_chars.remove(found_at);
-                    self.revealed.insert(*c);
-                    *lr = LetterResult::Contains(*c)
-                }
-            }
-        }
+        let letters = compare(&guess, &self.word);
+        self.revealed = self.revealed.clone().apply(&letters);
 
         let guesses = self.guesses.len() + 1;
 
@@ -152,17 +328,333 @@ impl Game {
             .all(|lr| matches!(lr, LetterResult::Exact(_)));
 
         let result = match guesses {
-            1..=5 if correct => GuessResult::Win,
-            1..=5 => GuessResult::Incorrect,
+            _ if correct => GuessResult::Win,
+            g if self.max_guesses.is_none_or(|max| g < max) => GuessResult::Incorrect,
             _ => GuessResult::Lose,
         };
 
         let verdict = CheckData {
             letters,
             result,
-            guesses: guesses.try_into().unwrap(),
+            guesses,
         };
         self.guesses.push(verdict.clone());
         verdict
     }
 }
+
+/// Picks the word the adversary commits to for `guess`: one from the largest group of `remaining`
+/// words that would all give the same feedback. Ties go to the feedback with fewer hits.
+fn hardest_word(guess: &str, remaining: &WordList) -> &'static str {
+    let mut words = remaining.get();
+    words.sort_unstable();
+    words
+        .iter()
+        .copied()
+        .into_group_map_by(|word| pattern(guess, word))
+        .into_iter()
+        .max_by_key(|(pattern, group)| (group.len(), Reverse(*pattern)))
+        .map_or("", |(_, group)| group[0])
+}
+
+/// Shortest supported word length.
+pub const MIN_LENGTH: usize = 4;
+/// Longest supported word length.
+pub const MAX_LENGTH: usize = 11;
+
+/// Number of distinct feedback patterns for a guess of `length` letters (3^length).
+pub fn pattern_count(length: usize) -> usize {
+    3_usize.pow(length as u32)
+}
+
+/// Scores `guess` against `word` the way the official game does: exact matches are claimed first,
+/// then the remaining letters are marked as contained, left to right, while unclaimed copies of
+/// that letter are left in the word.
+pub fn compare(guess: &str, word: &str) -> Vec<LetterResult> {
+    let mut word_chars = Vec::from_iter(word.chars());
+    let mut letters: Vec<LetterResult> = vec![];
+
+    for (i, c) in guess.chars().enumerate() {
+        letters.push(match word_chars.get_mut(i) {
+            Some(fc) if *fc == c => {
+                *fc = '_';
+                LetterResult::Exact(c)
+            }
+            _ => LetterResult::NotFound(c),
+        });
+    }
+
+    word_chars.sort_unstable();
+
+    for lr in letters.iter_mut() {
+        if let LetterResult::NotFound(c) = lr {
+            if let Ok(found_at) = word_chars.binary_search(c) {
+                word_chars.remove(found_at);
+                *lr = LetterResult::Contains(*c)
+            }
+        }
+    }
+
+    letters
+}
+
+/// Same rules as `compare` but for lowercase dictionary words of equal length, encoded as a base-3
+/// number (not found = 0, contains = 1, exact = 2) with the first letter as the most significant
+/// digit. This is the hot loop of the suggestion engine so it avoids allocating.
+pub fn pattern(guess: &str, word: &str) -> u32 {
+    let (guess, word) = (guess.as_bytes(), word.as_bytes());
+    let mut unclaimed = [0_u8; 26];
+    let mut digits = [0_u8; MAX_LENGTH];
+    let length = guess.len();
+
+    for i in 0..length {
+        if guess[i] == word[i] {
+            digits[i] = 2;
+        } else {
+            unclaimed[(word[i] - b'a') as usize] += 1;
+        }
+    }
+
+    for i in 0..length {
+        if digits[i] == 0 {
+            let count = &mut unclaimed[(guess[i] - b'a') as usize];
+            if *count > 0 {
+                *count -= 1;
+                digits[i] = 1;
+            }
+        }
+    }
+
+    digits[..length]
+        .iter()
+        .fold(0, |acc, d| acc * 3 + u32::from(*d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feedback::{parse_marks, pattern_of};
+
+    const MISSES: [&str; 6] = ["slate", "pious", "dumpy", "fight", "brown", "vexed"];
+
+    fn play(game: &mut Game, guesses: &[&str]) -> Vec<CheckData> {
+        guesses.iter().map(|g| game.check(g)).collect()
+    }
+
+    fn marks(marks: &str) -> u32 {
+        pattern_of(&parse_marks(marks).unwrap())
+    }
+
+    #[test]
+    fn patterns_for_the_shortest_and_longest_words() {
+        assert_eq!(pattern("toot", "tote"), marks("GG.Y"));
+        assert_eq!(pattern("otto", "tote"), marks("YYG."));
+        assert_eq!(pattern("programmers", "programming"), marks("GGGGGGGG..."));
+        assert_eq!(pattern("grammarians", "programming"), marks("YG.YYGYY.G."));
+    }
+
+    #[test]
+    fn games_with_the_shortest_and_longest_words() {
+        let mut game = Game::for_word("TOTE").set_word_list(WordList::from(vec!["toot", "tote"]));
+        assert!(matches!(game.check("toot").result, GuessResult::Incorrect));
+        assert!(matches!(game.check("TOTE").result, GuessResult::Win));
+
+        let mut game = Game::for_word("programming")
+            .set_word_list(WordList::from(vec!["programmers", "programming"]))
+            .set_hard_mode();
+        game.check("programmers");
+        assert!(matches!(game.check("programming").result, GuessResult::Win));
+        assert_eq!(game.score(), "2/6");
+    }
+
+    #[test]
+    fn win_on_every_turn() {
+        for turn in 1..=6 {
+            let mut game = Game::for_word("crane");
+            let results = play(&mut game, &MISSES[..turn - 1]);
+            assert!(results
+                .iter()
+                .all(|r| matches!(r.result, GuessResult::Incorrect)));
+
+            let last = game.check("crane");
+            assert!(matches!(last.result, GuessResult::Win), "turn {}", turn);
+            assert_eq!(last.guesses, turn);
+            assert_eq!(game.guesses().len(), turn);
+        }
+    }
+
+    #[test]
+    fn win_on_last_turn_of_custom_limit() {
+        let mut game = Game::for_word("crane").set_max_guesses(3);
+        play(&mut game, &MISSES[..2]);
+        assert!(matches!(game.check("crane").result, GuessResult::Win));
+    }
+
+    #[test]
+    fn lose_after_limit() {
+        let mut game = Game::for_word("crane");
+        let results = play(&mut game, &MISSES);
+        assert!(matches!(results[4].result, GuessResult::Incorrect));
+        assert!(matches!(results[5].result, GuessResult::Lose));
+        assert_eq!(results[5].guesses, 6);
+
+        let mut game = Game::for_word("crane").set_max_guesses(2);
+        let results = play(&mut game, &MISSES[..2]);
+        assert!(matches!(results[1].result, GuessResult::Lose));
+    }
+
+    #[test]
+    fn days_past_the_answer_list_have_no_puzzle() {
+        let answers = WordList::contest().word_count();
+        assert_eq!(
+            Game::new(Some(answers - 1)).unwrap().day(),
+            Some(answers - 1)
+        );
+        assert!(Game::new(Some(answers)).is_none());
+    }
+
+    #[test]
+    fn score_marks_a_loss_with_x() {
+        let mut game = Game::for_word("crane");
+        play(&mut game, &MISSES[..5]);
+        assert_eq!(game.score(), "5/6");
+        game.check("crane");
+        assert_eq!(game.score(), "6/6");
+
+        let mut game = Game::for_word("crane");
+        play(&mut game, &MISSES);
+        assert_eq!(game.score(), "X/6");
+    }
+
+    #[test]
+    fn calls_after_game_over_repeat_last_result() {
+        let mut game = Game::for_word("crane");
+        play(&mut game, &MISSES);
+        let after = game.check("crane");
+        assert!(matches!(after.result, GuessResult::Lose));
+        assert_eq!(after.guesses, 6);
+        assert_eq!(game.guesses().len(), 6);
+
+        let mut game = Game::for_word("crane");
+        game.check("crane");
+        let after = game.check("slate");
+        assert!(matches!(after.result, GuessResult::Win));
+        assert_eq!(game.guesses().len(), 1);
+    }
+
+    #[test]
+    fn unknown_word_does_not_use_a_turn() {
+        let mut game = Game::for_word("crane");
+        assert!(matches!(
+            game.check("zzzzz").result,
+            GuessResult::Unknown(_)
+        ));
+        assert!(game.guesses().is_empty());
+    }
+
+    #[test]
+    fn hard_mode_rejects_moved_green() {
+        let mut game = Game::for_word("crane").set_hard_mode();
+        game.check("trace");
+        let result = game.check("react");
+        assert!(matches!(
+            result.result,
+            GuessResult::Invalid(HardModeError::Position {
+                position: 1,
+                letter: 'r'
+            })
+        ));
+        assert_eq!(game.guesses().len(), 1);
+    }
+
+    #[test]
+    fn hard_mode_rejects_missing_letters() {
+        let mut game = Game::for_word("geese").set_hard_mode();
+        game.check("eerie");
+        let result = game.check("geode");
+        assert!(matches!(
+            result.result,
+            GuessResult::Invalid(HardModeError::Missing {
+                letter: 'e',
+                count: 3
+            })
+        ));
+        assert_eq!(
+            HardModeError::Missing {
+                letter: 'e',
+                count: 3
+            }
+            .to_string(),
+            "Guess must contain 3 copies of E"
+        );
+    }
+
+    #[test]
+    fn easy_mode_accepts_anything_in_the_list() {
+        let mut game = Game::for_word("crane");
+        game.check("trace");
+        assert!(matches!(game.check("pious").result, GuessResult::Incorrect));
+    }
+
+    #[test]
+    fn adversary_keeps_the_largest_group() {
+        let answers = WordList::from(vec!["crane", "crate", "goofy", "fuzzy", "jumpy"]);
+        let mut game = Game::adversarial(answers);
+        assert_eq!(game.check("plate").letters, compare("plate", "goofy"));
+
+        // Both words left give different feedback, so the adversary dodges the guess.
+        assert!(matches!(game.check("goofy").result, GuessResult::Incorrect));
+        assert!(matches!(game.check("fuzzy").result, GuessResult::Win));
+    }
+
+    #[test]
+    fn adversary_has_no_guess_limit() {
+        let mut game = Game::adversarial(WordList::contest());
+        assert_eq!(game.max_guesses(), None);
+        for _ in 0..300 {
+            assert!(matches!(game.check("fuzzy").result, GuessResult::Incorrect));
+        }
+        assert_eq!(game.guesses().last().unwrap().guesses, 300);
+        assert_eq!(game.score(), "300/∞");
+
+        let mut game = Game::adversarial(WordList::contest()).set_max_guesses(2);
+        game.check("fuzzy");
+        assert!(matches!(game.check("fuzzy").result, GuessResult::Lose));
+    }
+
+    #[test]
+    fn letter_states_keep_the_best_mark() {
+        let mut game = Game::for_word("crane");
+        game.check("nacre");
+        game.check("trace");
+        let states = game.letter_states();
+        assert_eq!(states[&'n'], Mark::Yellow);
+        assert_eq!(states[&'a'], Mark::Green);
+        assert_eq!(states[&'c'], Mark::Yellow);
+        assert_eq!(states[&'t'], Mark::Grey);
+        assert_eq!(states.get(&'z'), None);
+    }
+
+    #[test]
+    fn resumes_from_saved_state() {
+        let mut game = Game::for_word("crane").set_hard_mode();
+        game.check("trace");
+        let mut resumed: Game =
+            serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert_eq!(resumed.guesses().len(), 1);
+        assert!(matches!(
+            resumed.check("pious").result,
+            GuessResult::Invalid(_)
+        ));
+        assert!(matches!(resumed.check("crane").result, GuessResult::Win));
+    }
+
+    #[test]
+    fn duplicate_letters_are_scored_once() {
+        let letters = compare("eerie", "geese");
+        assert!(matches!(letters[0], LetterResult::Contains('e')));
+        assert!(matches!(letters[1], LetterResult::Exact('e')));
+        assert!(matches!(letters[4], LetterResult::Exact('e')));
+        assert_eq!(pattern("eerie", "geese"), 81 + 2 * 27 + 2);
+    }
+}
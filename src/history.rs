@@ -0,0 +1,225 @@
+//! The record of finished games kept between runs, and the statistics drawn from it.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::game::{Game, GuessResult};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+/// A finished game as stored in the history file, one JSON object per line.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// The puzzle number.
+    pub day: Option<usize>,
+    /// The secret word.
+    pub word: String,
+    /// Every counted guess, oldest first.
+    pub guesses: Vec<String>,
+    /// Whether the game was won.
+    pub won: bool,
+    /// Whether hard mode was on.
+    pub hard: bool,
+    /// Whether suggestions were shown during the game.
+    pub assisted: bool,
+}
+
+impl Record {
+    /// Returns `None` while the game is still in progress.
+    pub fn from_game(game: &Game, assisted: bool) -> Option<Self> {
+        let won = match game.result()? {
+            GuessResult::Win => true,
+            GuessResult::Lose => false,
+            _ => return None,
+        };
+        Some(Record {
+            day: game.day(),
+            word: game.word(),
+            guesses: game
+                .guesses()
+                .iter()
+                .map(|g| g.letters.iter().map(|l| l.to_char()).collect())
+                .collect(),
+            won,
+            hard: !game.is_easy(),
+            assisted,
+        })
+    }
+}
+
+/// Every game recorded in the history file.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    records: Vec<Record>,
+    skipped: usize,
+}
+
+/// Totals over the recorded games, as the web game shows them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Games finished.
+    pub played: usize,
+    /// Games won.
+    pub wins: usize,
+    /// Wins on consecutive puzzle days, up to the latest puzzle played.
+    pub current_streak: usize,
+    /// Longest run of wins on consecutive puzzle days.
+    pub max_streak: usize,
+    /// Number of games won with each guess count.
+    pub distribution: BTreeMap<usize, usize>,
+}
+
+impl Stats {
+    /// Share of games won, rounded down; 0 before any game is played.
+    pub fn win_percent(&self) -> usize {
+        (self.wins * 100).checked_div(self.played).unwrap_or(0)
+    }
+}
+
+/// Where wordless keeps its files: `$XDG_DATA_HOME/wordless`, falling back to
+/// `~/.local/share/wordless`.
+pub fn data_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .map(|data| data.join("wordless"))
+}
+
+impl History {
+    /// `history.jsonl` in the data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("history.jsonl"))
+    }
+
+    /// Reads one record per line. Lines that are not a record are skipped and counted, so a
+    /// damaged line does not cost the rest of the history.
+    pub fn parse(contents: &str) -> Self {
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .fold(History::default(), |mut history, line| {
+                match serde_json::from_str(line) {
+                    Ok(record) => history.records.push(record),
+                    Err(_) => history.skipped += 1,
+                }
+                history
+            })
+    }
+
+    /// Reads the history at `path`; a missing file is an empty history.
+    pub async fn load(path: &Path) -> Result<Self, std::io::Error> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(contents) => Ok(History::parse(&contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(History::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Adds `record` to the end of the file at `path`, creating it if needed.
+    pub async fn append(path: &Path, record: &Record) -> Result<(), std::io::Error> {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?
+            .write_all(line.as_bytes())
+            .await
+    }
+
+    /// Number of lines that could not be read as a record.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Whether puzzle `day` with secret `word` has already been recorded. Only one result per
+    /// puzzle counts, so replaying a finished puzzle does not pad the stats. The word tells apart
+    /// puzzles with the same number from different answer lists.
+    pub fn has_puzzle(&self, day: usize, word: &str) -> bool {
+        self.records
+            .iter()
+            .any(|r| r.day == Some(day) && r.word == word)
+    }
+
+    /// Streaks count wins on consecutive puzzle days, as in the web game: the games are taken in
+    /// day order and a skipped day ends the streak.
+    pub fn stats(&self) -> Stats {
+        let mut records = self.records.iter().collect::<Vec<&Record>>();
+        records.sort_by_key(|r| r.day);
+        records
+            .iter()
+            .zip(std::iter::once(None).chain(records.iter().map(|r| r.day)))
+            .fold(Stats::default(), |mut stats, (record, previous)| {
+                if let (Some(previous), Some(day)) = (previous, record.day) {
+                    if day > previous + 1 {
+                        stats.current_streak = 0;
+                    }
+                }
+                stats.played += 1;
+                if record.won {
+                    stats.wins += 1;
+                    stats.current_streak += 1;
+                    stats.max_streak = stats.max_streak.max(stats.current_streak);
+                    *stats.distribution.entry(record.guesses.len()).or_default() += 1;
+                } else {
+                    stats.current_streak = 0;
+                }
+                stats
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_from_history() {
+        let mut game = Game::for_word("crane");
+        game.check("slate");
+        assert_eq!(Record::from_game(&game, false), None);
+        game.check("crane");
+        let win = Record::from_game(&game, false).unwrap();
+        assert_eq!(win.guesses, vec!["slate", "crane"]);
+
+        let on_day = |day: usize, won: bool| Record {
+            day: Some(day),
+            won,
+            ..win.clone()
+        };
+        let contents = [
+            on_day(1, true),
+            on_day(2, true),
+            on_day(3, false),
+            on_day(4, true),
+        ]
+        .iter()
+        .map(|r| serde_json::to_string(r).unwrap())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+        let history = History::parse(&format!("{}\n{{\"day\":\n", contents));
+        assert_eq!(history.skipped(), 1);
+        let stats = history.stats();
+        assert_eq!(stats.played, 4);
+        assert_eq!(stats.win_percent(), 75);
+        assert_eq!(stats.current_streak, 1);
+        assert_eq!(stats.max_streak, 2);
+        assert_eq!(stats.distribution, BTreeMap::from([(2, 3)]));
+
+        // Played out of order, with day 4 missed.
+        let history = History {
+            records: [2, 5, 1, 3, 6].map(|day| on_day(day, true)).to_vec(),
+            skipped: 0,
+        };
+        let stats = history.stats();
+        assert_eq!(stats.current_streak, 2);
+        assert_eq!(stats.max_streak, 3);
+    }
+}
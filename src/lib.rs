@@ -0,0 +1,42 @@
+//! The Wordle engine and solver behind the `wordless` command line tool.
+//!
+//! - [`game`] scores guesses and enforces the rules: [`game::Game`] plays one puzzle,
+//!   [`multi::MultiGame`] several boards at once.
+//! - [`word_list`] holds the accepted guesses and the puzzle answers, and
+//!   [`constraints`] narrows them down to the words that fit the feedback seen so far.
+//! - [`suggest`] ranks the next guess; [`tree`] precomputes a whole strategy and
+//!   [`bench`](mod@bench) measures one against every answer.
+//! - [`feedback`] reads and writes rows of marks, and [`reverse`] works back from a share grid.
+//! - [`report`] is the schema of the structured output written by `--format json`.
+//!
+//! ```
+//! use wordless::constraints::eliminate_words;
+//! use wordless::game::{Game, GuessResult};
+//! use wordless::word_list::WordList;
+//!
+//! let mut game = Game::for_word("crane");
+//! let result = game.check("slate");
+//! assert!(matches!(result.result, GuessResult::Incorrect));
+//!
+//! let remaining = eliminate_words(WordList::contest(), result.letters);
+//! assert!(remaining.contains("crane"));
+//! assert!(!remaining.contains("slate"));
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod bench;
+pub mod constraints;
+pub mod dictionary_set;
+pub mod feedback;
+pub mod frequency;
+pub mod game;
+pub mod history;
+pub mod multi;
+pub mod patterns;
+pub mod report;
+pub mod reverse;
+pub mod save;
+pub mod suggest;
+pub mod tree;
+pub mod word_list;
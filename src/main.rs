@@ -1,19 +1,28 @@
-mod dictionary_set;
-#[allow(dead_code)]
-mod game;
-mod word_list;
-mod suggest;
-
-use std::{collections::BTreeSet, io::Write, iter::FromIterator};
-
-use crate::dictionary_set::DictionarySet;
-use crate::game::{Game, GuessResult};
-use clap::{ArgGroup, Parser};
-use game::{CheckData, LetterResult};
-use prettytable::{cell, row, Table};
+mod screen;
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::screen::SuggestPanel;
+use clap::{ArgEnum, ArgGroup, Parser, Subcommand};
+use devtimer::DevTime;
+use prettytable::{row, Table};
 use termcolor::{Color, ColorSpec, StandardStream, WriteColor};
-use word_list::WordList;
-use suggest::suggest;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines, Stdin};
+use wordless::bench::bench;
+use wordless::constraints::eliminate_words;
+use wordless::feedback::{format_marks, parse_marks, parse_row, Mark, Notation};
+use wordless::game::{self, CheckData, Game, GuessResult, LetterResult, MAX_LENGTH, MIN_LENGTH};
+use wordless::history::{History, Record};
+use wordless::multi::MultiGame;
+use wordless::report::{Report, Row};
+use wordless::reverse::{guesses_for_rows, possible_answers};
+use wordless::save;
+use wordless::suggest::{suggest, suggest_multi, AnswerPool, Ranking, Suggestion};
+use wordless::tree::DecisionTree;
+use wordless::word_list::WordList;
 
 #[derive(Parser, Debug)]
 #[clap(version)]
@@ -35,132 +44,871 @@ struct Args {
     #[clap(long, value_name = "COUNT", default_value = "20")]
     suggest_count: usize,
 
+    /// How to rank suggestions (used with "--suggest")
+    #[clap(long, arg_enum, default_value = "entropy")]
+    rank: RankArg,
+
+    /// Which words count as possible answers when ranking suggestions and counting the words
+    /// remaining (used with "--suggest")
+    #[clap(long, arg_enum, default_value = "contest")]
+    pool: PoolArg,
+
     /// Straight up cheat. You must supply this flag at least three times
     #[clap(long, parse(from_occurrences))]
     cheat: usize,
 
+    /// Number of guesses allowed before the game is lost; defaults to 6, to no limit for
+    /// "absurdle", or to the number of boards plus five for "multi"
+    #[clap(long, value_name = "COUNT")]
+    max_guesses: Option<usize>,
+
     ///Use easy mode
     #[clap(short, long)]
     easy: bool,
 
+    /// Only accept guesses that could be a puzzle answer
+    #[clap(long)]
+    strict: bool,
+
+    /// File of words accepted as guesses, one per line; defaults to the built-in list
+    #[clap(long, value_name = "FILE")]
+    guesses_file: Option<PathBuf>,
+
+    /// File of possible answers in puzzle order, one per line; defaults to the built-in list
+    #[clap(long, value_name = "FILE")]
+    answers_file: Option<PathBuf>,
+
+    /// Decision tree written by the "tree" command to look suggestions up in (used with
+    /// "--suggest")
+    #[clap(long, value_name = "FILE")]
+    tree: Option<PathBuf>,
+
+    /// File finished games are recorded in; defaults to history.jsonl in the user's data
+    /// directory
+    #[clap(long, value_name = "FILE")]
+    history: Option<PathBuf>,
+
+    /// How results are printed: colored rows and tables, or one JSON object per line for scripts
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+
     /// Your guesses
     guesses: Vec<String>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+impl Args {
+    fn max_guesses(&self) -> usize {
+        self.max_guesses.unwrap_or(6)
+    }
+
+    fn rank(&self) -> Ranking {
+        match self.rank {
+            RankArg::Entropy => Ranking::Entropy,
+            RankArg::WorstCase => Ranking::WorstCase,
+        }
+    }
+
+    fn pool(&self) -> AnswerPool {
+        match self.pool {
+            PoolArg::Contest => AnswerPool::Contest,
+            PoolArg::Full => AnswerPool::Full,
+            PoolArg::Weighted => AnswerPool::Weighted,
+        }
+    }
+}
+
+/// `Ranking` as a command-line value.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum RankArg {
+    /// Prefer the guess with the highest expected information
+    Entropy,
+    /// Prefer the guess whose largest feedback bucket is smallest
+    WorstCase,
+}
+
+/// `AnswerPool` as a command-line value.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum PoolArg {
+    /// Only puzzle answers, all equally likely
+    Contest,
+    /// Every accepted guess, all equally likely
+    Full,
+    /// Every accepted guess, weighted by how common the word is
+    Weighted,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// Colored rows, tables and charts
+    Text,
+    /// See `wordless::report` for the schema
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Play an interactive game, reading one guess per line from stdin
+    Play,
+
+    /// Play an interactive game full screen, with the suggestion panel alongside when used with
+    /// "--suggest"
+    Tui,
+
+    /// Play an interactive game against an opponent that picks no word up front and instead keeps
+    /// as many answers in play as it can after every guess (Absurdle). There is no guess limit
+    /// unless "--max-guesses" is given
+    Absurdle,
+
+    /// Play several boards at once with the same guesses (Dordle, Quordle, Octordle), reading one
+    /// guess per line from stdin. Allows the number of boards plus five guesses
+    Multi {
+        /// Number of boards; defaults to 4, or to one per "--answer"
+        #[clap(long, short)]
+        boards: Option<usize>,
+
+        /// Secret word for a board instead of the day's; give once per board
+        #[clap(long = "answer", value_name = "WORD")]
+        answers: Vec<String>,
+    },
+
+    /// Solve every puzzle answer by always taking the top suggestion and report how it went
+    Bench {
+        /// Only play the first COUNT answers
+        #[clap(long, value_name = "COUNT")]
+        limit: Option<usize>,
+
+        /// First guess to use instead of computing the top suggestion
+        #[clap(long, value_name = "WORD")]
+        opening: Option<String>,
+    },
+
+    /// Suggest guesses from the colors seen on another board instead of a known secret word
+    Assist {
+        /// Each guess with its colors as guess:marks, where marks are g (green), y (yellow) or
+        /// . (grey), e.g. crane:gy..g; the share grid emoji work too
+        rows: Vec<String>,
+    },
+
+    /// Precompute a decision tree covering every puzzle answer and save it for "--tree"
+    Tree {
+        /// Where to write the tree
+        #[clap(long, short, value_name = "FILE")]
+        output: PathBuf,
+
+        /// Number of guesses to explore at each step; higher is slower but finds better trees
+        #[clap(long, default_value = "1")]
+        width: usize,
+    },
+
+    /// List the answers consistent with a shared emoji grid; with --day or --word, list the
+    /// guesses that could have produced each row
+    Reverse {
+        /// Rows of the grid, e.g. ⬛🟨⬛⬛🟩; a pasted multi-line grid works too
+        rows: Vec<String>,
+    },
+
+    /// Show played count, win percentage, streaks and guess distribution from the history file
+    Stats,
 }
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<(), std::io::Error> {
     let config = Args::parse();
+    if config.guesses_file.is_some() || config.answers_file.is_some() {
+        let lists = async {
+            let guesses = load_word_list(config.guesses_file.as_deref()).await?;
+            let answers = load_word_list(config.answers_file.as_deref()).await?;
+            WordList::set_lists(guesses, answers)
+        };
+        if let Err(e) = lists.await {
+            return print_error(&config, format!("Could not load the word lists: {}.", e));
+        }
+    }
+
+    if let Some(Command::Stats) = &config.command {
+        return print_stats(&config).await;
+    }
+
+    if let Some(Command::Tree { output, width }) = &config.command {
+        return build_tree(&config, output, *width).await;
+    }
+
+    let tree = match &config.tree {
+        Some(path) => {
+            let tree: DecisionTree = serde_json::from_str(&tokio::fs::read_to_string(path).await?)?;
+            if tree.hard == config.easy {
+                return print_error(
+                    &config,
+                    format!(
+                        "The decision tree was built for {} mode.",
+                        if tree.hard { "hard" } else { "easy" }
+                    ),
+                );
+            }
+            Some(tree)
+        }
+        None => None,
+    };
+
+    if let Some(Command::Bench { limit, opening }) = &config.command {
+        return run_bench(&config, *limit, opening.as_deref());
+    }
+
+    if let Some(Command::Multi { boards, answers }) = &config.command {
+        let answers = answers
+            .iter()
+            .map(|a| a.to_ascii_lowercase())
+            .collect::<Vec<String>>();
+        if let Some(answer) = answers.iter().find(|a| !WordList::new().contains(a)) {
+            return print_error(
+                &config,
+                format!("Answer '{}' is not in the word list.", answer),
+            );
+        }
+        let mut game = match (*boards, answers.len()) {
+            (Some(0), _) => {
+                return print_error(&config, "There must be at least one board.".into())
+            }
+            (Some(boards), given) if given > 0 && given != boards => {
+                return print_error(
+                    &config,
+                    format!("{} answers were given for {} boards.", given, boards),
+                )
+            }
+            (boards, 0) => MultiGame::new(config.day, boards.unwrap_or(4)),
+            _ => MultiGame::for_words(&answers.iter().map(|a| a.as_str()).collect::<Vec<&str>>()),
+        };
+        if let Some(max_guesses) = config.max_guesses {
+            game = game.set_max_guesses(max_guesses);
+        }
+        if !config.easy {
+            game = game.set_hard_mode();
+        }
+        if config.strict {
+            game = game.set_word_list(WordList::contest());
+        }
+        if config.cheat >= 3 && config.format == Format::Text {
+            let words = game
+                .boards()
+                .iter()
+                .map(|b| b.word())
+                .collect::<Vec<String>>();
+            println!("Today's secret words are: {:?}\n", words);
+        }
+        return play_multi(game, &config).await;
+    }
+
+    if let Some(Command::Assist { rows }) = &config.command {
+        return assist(&config, tree.as_ref(), rows);
+    }
+
+    if let Some(Command::Reverse { rows }) = &config.command {
+        return reverse_solve(&config, rows);
+    }
+
     let day_opt = config.day;
-    let mut game = config
-        .word
-        .map(|w| Game::for_word(&*w))
-        .unwrap_or_else(|| Game::new(day_opt));
+    let mut game = match (&config.command, &config.word) {
+        (Some(Command::Absurdle), _) => Game::adversarial(WordList::contest()),
+        (_, Some(w)) => {
+            let word = w.to_ascii_lowercase();
+            if !(MIN_LENGTH..=MAX_LENGTH).contains(&word.len())
+                || !word.chars().all(|c| c.is_ascii_lowercase())
+            {
+                return print_error(
+                    &config,
+                    format!(
+                        "Word '{}' must have {} to {} letters from a to z.",
+                        w, MIN_LENGTH, MAX_LENGTH
+                    ),
+                );
+            }
+            Game::for_word(&word)
+        }
+        (_, None) => match Game::new(day_opt) {
+            Some(game) => game,
+            None => {
+                return print_error(
+                    &config,
+                    format!(
+                        "There is no puzzle for that day; the answer list has {} words.",
+                        WordList::contest().word_count()
+                    ),
+                )
+            }
+        },
+    };
 
+    if let Some(max_guesses) = config.max_guesses {
+        game = game.set_max_guesses(max_guesses);
+    }
     if !config.easy {
         game = game.set_hard_mode();
     }
 
-    if config.cheat >= 3 {
+    if config.strict {
+        game = game.set_word_list(WordList::contest());
+    }
+
+    let interactive = match config.command {
+        Some(Command::Play | Command::Tui | Command::Absurdle) => true,
+        None => config.guesses.is_empty(),
+        _ => false,
+    };
+    let save_path = game
+        .day()
+        .filter(|_| interactive)
+        .and_then(save::path_for_day);
+    if let Some(saved) = match &save_path {
+        Some(path) => save::load(path).await?,
+        None => None,
+    } {
+        if let Some(GuessResult::Win | GuessResult::Lose) = saved.result() {
+            if config.format == Format::Text {
+                println!("You have already finished this puzzle.\n");
+            }
+            return print_results(&config, &saved);
+        }
+        game = saved;
+        if config.strict {
+            game = game.set_word_list(WordList::contest());
+        }
+    }
+
+    if config.cheat >= 3 && !game.is_adversarial() && config.format == Format::Text {
         println!("Today's secret word is: {:?}\n", game.word());
     }
 
-    let invalid_guesses: Vec<&String> = config.guesses.iter().filter(|g| g.len() != 5).collect();
-    if !invalid_guesses.is_empty() {
-        println!("Invalid guesses: {:?}", invalid_guesses);
+    if let Some(Command::Tui) = config.command {
+        let panel = config.suggest.then_some(SuggestPanel {
+            count: config.suggest_count,
+            easy: config.easy,
+            ranking: config.rank(),
+            pool: config.pool(),
+        });
+        let game = screen::run(game, panel, save_path.as_deref()).await?;
+        if let Some(GuessResult::Win | GuessResult::Lose) = game.result() {
+            print_results(&config, &game)?;
+            record_game(&config, &game).await?;
+        }
         return Ok(());
     }
 
+    if interactive {
+        return play(game, &config, tree.as_ref(), save_path.as_deref()).await;
+    }
+
+    let invalid_guesses: Vec<&String> = config
+        .guesses
+        .iter()
+        .filter(|g| g.len() != game.word_length())
+        .collect();
+    if !invalid_guesses.is_empty() {
+        return print_error(&config, format!("Invalid guesses: {:?}", invalid_guesses));
+    }
+
     let (result, word_list) = config.guesses.iter().map(|g| g.to_ascii_lowercase()).fold(
         (GuessResult::Incorrect, WordList::new()),
         |(prev_result, word_list), guess| match prev_result {
-            GuessResult::Win | GuessResult::Lose | GuessResult::Invalid(_) => {
-                (prev_result, word_list)
-            }
+            GuessResult::Win
+            | GuessResult::Lose
+            | GuessResult::Invalid(_)
+            | GuessResult::Unknown(_) => (prev_result, word_list),
             GuessResult::Incorrect => {
                 let result = game.check(&guess);
-                print_single_guess(&result).unwrap();
+                if let GuessResult::Invalid(_) | GuessResult::Unknown(_) = result.result {
+                    return (result.result, word_list);
+                }
+                print_single_guess(&config, &result.letters).unwrap();
                 (result.result, eliminate_words(word_list, result.letters))
             }
         },
     );
 
-    println!();
+    if config.format == Format::Text {
+        println!();
+    }
 
     match result {
-        GuessResult::Win => print_results(&game, config.suggest)?,
+        GuessResult::Win | GuessResult::Lose => {
+            print_results(&config, &game)?;
+            record_game(&config, &game).await?;
+        }
         GuessResult::Incorrect => {
+            if !config.guesses.is_empty() {
+                print_keyboard(&config, &game)?;
+            }
             if config.suggest {
-                print_suggestion(
-                    config.suggest_count,
-                    &suggest(
-                        DictionarySet::from_word_list(&word_list),
-                        word_list,
-                        config.easy,
-                    )?,
-                )?;
+                print_next_move(&config, tree.as_ref(), &game.guesses(), word_list)?;
             }
         }
-        GuessResult::Lose => print_results(&game, config.suggest)?,
-        GuessResult::Invalid(w) => println!("Guess '{}' does not contain all revealed letters.", w),
+        GuessResult::Invalid(e) => print_error(&config, format!("Hard mode: {}.", e))?,
+        GuessResult::Unknown(w) => {
+            print_error(&config, format!("Guess '{}' is not in the word list.", w))?
+        }
     }
 
     Ok(())
 }
 
-fn eliminate_words(word_list: WordList, letters: Vec<LetterResult>) -> WordList {
-    let set = DictionarySet::from_word_list(&word_list);
-    let found_letters =
-        BTreeSet::from_iter(letters.iter().filter(|r| r.is_found()).map(|r| r.to_char()));
+async fn play(
+    mut game: Game,
+    config: &Args,
+    tree: Option<&DecisionTree>,
+    save_path: Option<&Path>,
+) -> Result<(), std::io::Error> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut word_list = WordList::new();
 
-    letters
-        .iter()
-        .enumerate()
-        .fold(word_list, |word_list, (i, result)| match result {
-            LetterResult::Exact(c) => position_match(word_list, &set, i, c),
-            LetterResult::Contains(c) => position_mismatch(word_list, &set, i, c).ensure_letter(*c),
-            LetterResult::NotFound(c) if !found_letters.contains(c) => {
-                //TODO: This is not entirely correct, doesn't remove duplicates that aren't found
-                word_list.remove_letter(*c)
+    // Pick up where a saved game left off.
+    let played = game.guesses();
+    for result in &played {
+        print_single_guess(config, &result.letters)?;
+        word_list = eliminate_words(word_list, result.letters.clone());
+    }
+    if !played.is_empty() {
+        if config.format == Format::Text {
+            println!();
+        }
+        print_keyboard(config, &game)?;
+    }
+    if config.suggest {
+        print_next_move(config, tree, &played, word_list.clone())?;
+    }
+
+    while let Some(guess) = read_guess(&mut lines, config, game.word_length()).await? {
+        let result = game.check(&guess);
+        if let (Some(path), GuessResult::Win | GuessResult::Lose | GuessResult::Incorrect) =
+            (save_path, &result.result)
+        {
+            save::store(path, &game).await?;
+        }
+        match result.result {
+            GuessResult::Win | GuessResult::Lose => {
+                print_single_guess(config, &result.letters)?;
+                if config.format == Format::Text {
+                    println!();
+                }
+                print_results(config, &game)?;
+                return record_game(config, &game).await;
             }
-            _ => word_list,
-        })
+            GuessResult::Incorrect => {
+                print_single_guess(config, &result.letters)?;
+                if config.format == Format::Text {
+                    println!();
+                }
+                print_keyboard(config, &game)?;
+                word_list = eliminate_words(word_list, result.letters);
+                if config.suggest {
+                    print_next_move(config, tree, &game.guesses(), word_list.clone())?;
+                }
+            }
+            GuessResult::Invalid(e) => print_error(config, format!("Hard mode: {}.", e))?,
+            GuessResult::Unknown(w) => {
+                print_error(config, format!("Guess '{}' is not in the word list.", w))?
+            }
+        }
+    }
+
+    Ok(())
 }
 
-fn position_match(word_list: WordList, set: &DictionarySet, p: usize, c: &char) -> WordList {
-    word_list.intersect(get_position_vec(set, p, c))
+/// Prompts until a guess of `length` letters is entered, lowercased. `None` at the end of input.
+async fn read_guess(
+    lines: &mut Lines<BufReader<Stdin>>,
+    config: &Args,
+    length: usize,
+) -> Result<Option<String>, std::io::Error> {
+    let mut stdout = tokio::io::stdout();
+    loop {
+        if config.format == Format::Text {
+            stdout.write_all(b"> ").await?;
+            stdout.flush().await?;
+        }
+
+        let guess = match lines.next_line().await? {
+            Some(line) => line.trim().to_ascii_lowercase(),
+            None => return Ok(None),
+        };
+
+        if guess.is_empty() {
+            continue;
+        }
+
+        if guess.len() != length {
+            print_error(config, format!("Invalid guess: {:?}", guess))?;
+            continue;
+        }
+
+        return Ok(Some(guess));
+    }
 }
 
-fn position_mismatch(word_list: WordList, set: &DictionarySet, p: usize, c: &char) -> WordList {
-    word_list.subtract(get_position_vec(set, p, c))
+async fn play_multi(mut game: MultiGame, config: &Args) -> Result<(), std::io::Error> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut word_lists = vec![WordList::new(); game.boards().len()];
+
+    while let Some(guess) = read_guess(&mut lines, config, game.word_length()).await? {
+        let (result, rows) = game.check(&guess);
+        match result {
+            GuessResult::Invalid(e) => {
+                print_error(config, format!("Hard mode: {}.", e))?;
+                continue;
+            }
+            GuessResult::Unknown(w) => {
+                print_error(config, format!("Guess '{}' is not in the word list.", w))?;
+                continue;
+            }
+            _ => {}
+        }
+
+        print_multi_guess(config, &rows, game.word_length())?;
+        for (word_list, row) in word_lists.iter_mut().zip(rows) {
+            match row {
+                Some(row) if matches!(row.result, GuessResult::Win) => {
+                    *word_list = WordList::from(vec![])
+                }
+                Some(row) => *word_list = eliminate_words(word_list.clone(), row.letters),
+                None => {}
+            }
+        }
+
+        match result {
+            GuessResult::Win | GuessResult::Lose => {
+                if config.format == Format::Text {
+                    println!();
+                }
+                return print_multi_results(config, &game);
+            }
+            _ => {
+                if config.suggest {
+                    let remaining = word_lists
+                        .iter()
+                        .map(|w| config.pool().answers(w).word_count())
+                        .sum();
+                    let revealed = game
+                        .boards()
+                        .iter()
+                        .map(|board| board.revealed().clone())
+                        .collect::<Vec<_>>();
+                    match suggest_multi(
+                        word_lists.clone(),
+                        &revealed,
+                        config.easy,
+                        config.rank(),
+                        config.pool(),
+                    ) {
+                        Ok(suggestions) => print_suggestion(config, remaining, &suggestions)?,
+                        Err(e) => print_error(config, format!("Nothing to suggest: {}.", e))?,
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
 
-fn get_position_vec(set: &DictionarySet, p: usize, c: &char) -> Vec<&'static str> {
-    Vec::from_iter(set.list_for_position(p).get(c).unwrap())
+fn assist(
+    config: &Args,
+    tree: Option<&DecisionTree>,
+    rows: &[String],
+) -> Result<(), std::io::Error> {
+    let mut word_list = WordList::new();
+    let mut played = vec![];
+    for row in rows {
+        let letters = match parse_row(row) {
+            Ok(letters) => letters,
+            Err(e) => return print_error(config, format!("Invalid row: {}", e)),
+        };
+        print_single_guess(config, &letters)?;
+        word_list = eliminate_words(word_list, letters.clone());
+        played.push(letters);
+    }
+
+    if config.format == Format::Text {
+        println!();
+    }
+    print_next_move_for(config, tree.and_then(|t| t.next_guess(&played)), word_list)
+}
+
+/// Looks the next guess up in the decision tree if there is one and the game is still on it,
+/// otherwise prints the suggestion table.
+fn print_next_move(
+    config: &Args,
+    tree: Option<&DecisionTree>,
+    guesses: &[CheckData],
+    word_list: WordList,
+) -> Result<(), std::io::Error> {
+    let rows = guesses
         .iter()
-        .map(|x| **x)
-        .collect()
+        .map(|g| g.letters.clone())
+        .collect::<Vec<Vec<LetterResult>>>();
+    print_next_move_for(config, tree.and_then(|t| t.next_guess(&rows)), word_list)
+}
+
+fn print_next_move_for(
+    config: &Args,
+    next: Option<&str>,
+    word_list: WordList,
+) -> Result<(), std::io::Error> {
+    let answers = config.pool().answers(&word_list);
+    if let Some(guess) = next {
+        if config.format == Format::Json {
+            return print_json(&Report::TreeGuess {
+                remaining: answers.word_count(),
+                word: guess.to_string(),
+            });
+        }
+        println!("Words remaining: {}", answers.word_count());
+        println!("Next guess from the decision tree: {}", guess);
+        return Ok(());
+    }
+
+    match suggest(word_list, config.easy, config.rank(), config.pool()) {
+        Ok(suggestions) => print_suggestion(config, answers.word_count(), &suggestions),
+        Err(e) => print_error(config, format!("Nothing to suggest: {}.", e)),
+    }
+}
+
+async fn build_tree(config: &Args, output: &Path, width: usize) -> Result<(), std::io::Error> {
+    let mut answers = WordList::contest().get();
+    answers.sort_unstable();
+    let mut guesses = WordList::new().get();
+    guesses.sort_unstable();
+
+    let mut timer = DevTime::new_simple();
+    timer.start();
+    let tree = match DecisionTree::build(
+        &answers,
+        &guesses,
+        !config.easy,
+        config.max_guesses(),
+        width,
+    ) {
+        Some(tree) => tree,
+        None => {
+            return print_error(
+                config,
+                "There are no answers to build a tree for.".to_string(),
+            )
+        }
+    };
+    timer.stop();
+
+    tokio::fs::write(output, serde_json::to_string(&tree)?).await?;
+    let seconds = timer.time_in_millis().unwrap_or(0) as f64 / 1000.0;
+    if config.format == Format::Json {
+        return print_json(&Report::tree(&tree, seconds));
+    }
+    println!("Opening:  {}", tree.root.guess);
+    println!("Answers:  {}", tree.answers);
+    println!("Mean:     {:.3}", tree.mean());
+    println!("Failures: {}", tree.failures);
+    println!("Time:     {:.2}s", seconds);
+    Ok(())
+}
+
+fn reverse_solve(config: &Args, rows: &[String]) -> Result<(), std::io::Error> {
+    let mut grid = vec![];
+    for line in rows.iter().flat_map(|row| row.lines()) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_marks(line) {
+            Ok(marks) => grid.push(marks),
+            Err(e) => return print_error(config, format!("Invalid row: {}", e)),
+        }
+    }
+
+    let guesses = WordList::new().get();
+    let answer = config
+        .word
+        .as_ref()
+        .map(|w| w.to_ascii_lowercase())
+        .or_else(|| {
+            config
+                .day
+                .and_then(WordList::get_word_for_day)
+                .map(String::from)
+        });
+
+    match answer {
+        Some(answer) => {
+            let rows = match guesses_for_rows(&grid, &answer, &guesses) {
+                Ok(rows) => rows,
+                Err(e) => return print_error(config, format!("Invalid answer: {}", e)),
+            };
+            for (row, words) in grid.iter().zip(rows) {
+                if config.format == Format::Json {
+                    print_json(&Report::RowGuesses {
+                        marks: row.clone(),
+                        guesses: words.iter().map(|w| w.to_string()).collect(),
+                    })?;
+                    continue;
+                }
+                let row: String = row.iter().map(|m| m.to_char(Notation::Emoji)).collect();
+                println!("{} {} words: {}", row, words.len(), words.join(" "));
+            }
+        }
+        None => {
+            let answers = match possible_answers(&grid, &WordList::contest().get(), &guesses) {
+                Ok(answers) => answers,
+                Err(e) => return print_error(config, format!("Invalid row: {}", e)),
+            };
+            if config.format == Format::Json {
+                return print_json(&Report::PossibleAnswers {
+                    answers: answers.iter().map(|w| w.to_string()).collect(),
+                });
+            }
+            println!("Possible answers: {}", answers.len());
+            println!("{}", answers.join(" "));
+        }
+    }
+    Ok(())
+}
+
+fn run_bench(
+    config: &Args,
+    limit: Option<usize>,
+    opening: Option<&str>,
+) -> Result<(), std::io::Error> {
+    let opening = match opening {
+        Some(word) => match WordList::new().find(&word.to_ascii_lowercase()) {
+            Some(word) => Some(word),
+            None => {
+                return print_error(
+                    config,
+                    format!("Opening '{}' is not in the word list.", word),
+                )
+            }
+        },
+        None => None,
+    };
+
+    let answers = WordList::contest();
+    let mut answers = answers.get();
+    answers.sort_unstable();
+    answers.truncate(limit.unwrap_or(answers.len()));
+
+    let mut timer = DevTime::new_simple();
+    timer.start();
+    let report = match bench(
+        answers,
+        opening,
+        config.easy,
+        config.rank(),
+        config.pool(),
+        config.max_guesses(),
+    ) {
+        Ok(report) => report,
+        Err(e) => return print_error(config, format!("Bench stopped: {}.", e)),
+    };
+    timer.stop();
+    let seconds = timer.time_in_millis().unwrap_or(0) as f64 / 1000.0;
+    if config.format == Format::Json {
+        return print_json(&Report::bench(&report, seconds));
+    }
+
+    let games = report.games();
+    let most = report.histogram.values().copied().max().unwrap_or(0).max(1);
+    for guesses in 1..=config.max_guesses() {
+        let count = report.histogram.get(&guesses).copied().unwrap_or(0);
+        println!(
+            "{:>2} | {:<40} {}",
+            guesses,
+            "#".repeat(count * 40 / most),
+            count
+        );
+    }
+    println!();
+    println!("Games:    {}", games);
+    println!("Mean:     {:.3}", report.mean());
+    println!("Failures: {} {:?}", report.failures.len(), report.failures);
+    println!("Time:     {:.2}s", seconds);
+    Ok(())
 }
 
 fn print_suggestion(
-    count: usize,
-    reduction: &Vec<(&str, usize, i64)>,
+    config: &Args,
+    remaining: usize,
+    reduction: &[Suggestion],
 ) -> Result<(), std::io::Error> {
+    let count = config.suggest_count;
+    if config.format == Format::Json {
+        return print_json(&Report::suggestions(remaining, reduction, count));
+    }
+    println!("Words remaining: {}", remaining);
     let mut table = Table::new();
-    table.add_row(row!["Word", "Remaining", "Pos Score"]);
-    for (word, remaining, score) in reduction.iter().take(count) {
-        table.add_row(row![word, remaining, score]);
+    table.add_row(row![
+        "Word",
+        "Remaining",
+        "Entropy",
+        "Frequency",
+        "Pos Score"
+    ]);
+    for suggestion in reduction.iter().take(count) {
+        table.add_row(row![
+            suggestion.word,
+            suggestion.remaining,
+            format!("{:.3}", suggestion.entropy),
+            suggestion.frequency,
+            suggestion.score
+        ]);
     }
     if reduction.len() > count {
-        table.add_row(row!["...", "", ""]);
+        table.add_row(row!["...", "", "", "", ""]);
     }
 
     table.printstd();
     Ok(())
 }
 
-fn print_single_guess(result: &CheckData) -> Result<(), std::io::Error> {
+fn print_single_guess(config: &Args, letters: &[LetterResult]) -> Result<(), std::io::Error> {
+    if config.format == Format::Json {
+        return print_json(&Report::guess(letters));
+    }
+    let mut stdout = StandardStream::stdout(termcolor::ColorChoice::Auto);
+    write_letters(&mut stdout, letters)?;
+    writeln!(&mut stdout)?;
+    Ok(())
+}
+
+/// Prints one guess on every board side by side, leaving a gap for boards already solved.
+fn print_multi_guess(
+    config: &Args,
+    rows: &[Option<CheckData>],
+    length: usize,
+) -> Result<(), std::io::Error> {
+    if config.format == Format::Json {
+        return print_json(&Report::MultiGuess {
+            boards: rows
+                .iter()
+                .map(|row| row.as_ref().map(|row| Row::new(&row.letters)))
+                .collect(),
+        });
+    }
     let mut stdout = StandardStream::stdout(termcolor::ColorChoice::Auto);
-    for letter in result.letters.iter() {
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            write!(&mut stdout, "  ")?;
+        }
+        match row {
+            Some(row) => write_letters(&mut stdout, &row.letters)?,
+            None => write!(&mut stdout, "{}", " ".repeat(length * 3))?,
+        }
+    }
+    writeln!(&mut stdout)?;
+    Ok(())
+}
+
+fn write_letters(
+    stdout: &mut StandardStream,
+    letters: &[LetterResult],
+) -> Result<(), std::io::Error> {
+    for letter in letters.iter() {
         match letter {
             game::LetterResult::Exact(c) => {
                 stdout.set_color(
@@ -169,7 +917,7 @@ fn print_single_guess(result: &CheckData) -> Result<(), std::io::Error> {
                         .set_fg(Some(Color::Black))
                         .set_bg(Some(Color::Green)),
                 )?;
-                write!(&mut stdout, " {} ", c.to_ascii_uppercase())?;
+                write!(stdout, " {} ", c.to_ascii_uppercase())?;
                 stdout.reset()?;
             }
             game::LetterResult::Contains(c) => {
@@ -179,7 +927,7 @@ fn print_single_guess(result: &CheckData) -> Result<(), std::io::Error> {
                         .set_fg(Some(Color::Black))
                         .set_bg(Some(Color::Yellow)),
                 )?;
-                write!(&mut stdout, " {} ", c.to_ascii_uppercase())?;
+                write!(stdout, " {} ", c.to_ascii_uppercase())?;
                 stdout.reset()?;
             }
             game::LetterResult::NotFound(c) => {
@@ -188,44 +936,214 @@ fn print_single_guess(result: &CheckData) -> Result<(), std::io::Error> {
                         .set_fg(Some(Color::White))
                         .set_bg(Some(Color::Black)),
                 )?;
-                write!(&mut stdout, " {} ", c.to_ascii_uppercase())?;
+                write!(stdout, " {} ", c.to_ascii_uppercase())?;
                 stdout.reset()?;
             }
         }
     }
-    writeln!(&mut stdout)?;
     Ok(())
 }
 
-fn print_results(game: &Game, assisted: bool) -> Result<(), std::io::Error> {
+/// Shows every letter on a QWERTY layout colored by what the guesses so far revealed about it.
+/// Left out of JSON output, where the rows already carry every letter's state.
+fn print_keyboard(config: &Args, game: &Game) -> Result<(), std::io::Error> {
+    if config.format == Format::Json {
+        return Ok(());
+    }
+    let states = game.letter_states();
+    let mut stdout = StandardStream::stdout(termcolor::ColorChoice::Auto);
+    for (indent, row) in [(0, "qwertyuiop"), (1, "asdfghjkl"), (3, "zxcvbnm")] {
+        write!(stdout, "{:indent$}", "", indent = indent)?;
+        for c in row.chars() {
+            let mut color = ColorSpec::new();
+            match states.get(&c) {
+                Some(Mark::Green) => color
+                    .set_intense(true)
+                    .set_fg(Some(Color::Black))
+                    .set_bg(Some(Color::Green)),
+                Some(Mark::Yellow) => color
+                    .set_intense(true)
+                    .set_fg(Some(Color::Black))
+                    .set_bg(Some(Color::Yellow)),
+                Some(Mark::Grey) => color.set_fg(Some(Color::White)).set_bg(Some(Color::Black)),
+                None => color.set_fg(Some(Color::Black)).set_bg(Some(Color::White)),
+            };
+            stdout.set_color(&color)?;
+            write!(stdout, " {} ", c.to_ascii_uppercase())?;
+            stdout.reset()?;
+        }
+        writeln!(stdout)?;
+    }
+    writeln!(stdout)?;
+    Ok(())
+}
+
+fn print_results(config: &Args, game: &Game) -> Result<(), std::io::Error> {
+    let assisted = config.suggest;
+    if config.format == Format::Json {
+        return match Report::result(game, assisted) {
+            Some(report) => print_json(&report),
+            None => Ok(()),
+        };
+    }
+    let name = if game.is_adversarial() {
+        "Absurdle"
+    } else {
+        "Wordle"
+    };
     let num_str = game.day().map(|x| x.to_string()).unwrap_or("".to_string());
     let hard_str = if game.is_easy() { "" } else { "*" };
     let assisted_str = if assisted { " TA" } else { "" };
-    let guesses = game.guesses();
     println!(
-        "Wordle {} {}/6{}{}\n",
+        "{} {} {}{}{}\n",
+        name,
         num_str,
-        guesses.len(),
+        game.score(),
         hard_str,
         assisted_str
     );
-    for result in guesses {
+    for result in game.guesses() {
         print_single_result_no_spoiler(&result)?;
     }
     Ok(())
 }
 
-fn print_single_result_no_spoiler(result: &CheckData) -> Result<(), std::io::Error> {
-    let mut stdout = StandardStream::stdout(termcolor::ColorChoice::Auto);
+/// Appends a finished daily puzzle to the history file, the first time it is finished only.
+/// Games played with "--word" can be replayed at will and Absurdle games have no word of their
+/// own, so both are left out.
+async fn record_game(config: &Args, game: &Game) -> Result<(), std::io::Error> {
+    let path = match config.history.clone().or_else(History::default_path) {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let record = match Record::from_game(game, config.suggest) {
+        Some(record) if !game.is_adversarial() => record,
+        _ => return Ok(()),
+    };
+    match record.day {
+        Some(day) if !History::load(&path).await?.has_puzzle(day, &record.word) => {
+            History::append(&path, &record).await
+        }
+        _ => Ok(()),
+    }
+}
 
-    for letter in result.letters.iter() {
-        match letter {
-            LetterResult::Exact(_) => write!(&mut stdout, "🟩")?,
-            LetterResult::Contains(_) => write!(&mut stdout, "🟨")?,
-            LetterResult::NotFound(_) => write!(&mut stdout, "⬛")?,
-        };
+async fn print_stats(config: &Args) -> Result<(), std::io::Error> {
+    let history = match config.history.clone().or_else(History::default_path) {
+        Some(path) => History::load(&path).await?,
+        None => History::default(),
+    };
+    if history.skipped() > 0 {
+        eprintln!(
+            "Skipped {} unreadable line(s) in the history file.",
+            history.skipped()
+        );
+    }
+    let stats = history.stats();
+    if config.format == Format::Json {
+        return print_json(&Report::stats(&stats));
+    }
+    println!(
+        "Played {}  Win % {}  Current Streak {}  Max Streak {}\n",
+        stats.played,
+        stats.win_percent(),
+        stats.current_streak,
+        stats.max_streak
+    );
+
+    println!("Guess Distribution");
+    let rows = stats
+        .distribution
+        .keys()
+        .last()
+        .map_or(config.max_guesses(), |&g| g.max(config.max_guesses()));
+    let most = stats
+        .distribution
+        .values()
+        .max()
+        .copied()
+        .unwrap_or(0)
+        .max(1);
+    let mut stdout = StandardStream::stdout(termcolor::ColorChoice::Auto);
+    for guesses in 1..=rows {
+        let count = stats.distribution.get(&guesses).copied().unwrap_or(0);
+        let width = 1 + count * 30 / most;
+        write!(stdout, "{} ", guesses)?;
+        stdout.set_color(
+            ColorSpec::new()
+                .set_fg(Some(Color::White))
+                .set_bg(Some(Color::Ansi256(8))),
+        )?;
+        write!(stdout, "{:>width$}", count, width = width)?;
         stdout.reset()?;
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+fn print_multi_results(config: &Args, game: &MultiGame) -> Result<(), std::io::Error> {
+    let assisted = config.suggest;
+    if config.format == Format::Json {
+        return print_json(&Report::multi_result(game, assisted));
+    }
+    let name = match game.boards().len() {
+        2 => "Dordle".to_string(),
+        4 => "Quordle".to_string(),
+        8 => "Octordle".to_string(),
+        n => format!("{}-board Wordle", n),
+    };
+    let num_str = game.day().map(|x| format!(" {}", x)).unwrap_or_default();
+    let assisted_str = if assisted { " TA" } else { "" };
+    println!("{}{}{}\n", name, num_str, assisted_str);
+    for (i, board) in game.boards().iter().enumerate() {
+        match board.result() {
+            Some(GuessResult::Win) => println!(
+                "{}. {} {}/{}",
+                i + 1,
+                board.word().to_ascii_uppercase(),
+                board.guesses().len(),
+                game.max_guesses()
+            ),
+            _ => println!(
+                "{}. {} X/{}",
+                i + 1,
+                board.word().to_ascii_uppercase(),
+                game.max_guesses()
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn print_single_result_no_spoiler(result: &CheckData) -> Result<(), std::io::Error> {
+    println!("{}", format_marks(&result.letters, Notation::Emoji));
+    Ok(())
+}
+
+/// Reads the word list at `path`, if one was given.
+async fn load_word_list(path: Option<&Path>) -> Result<Option<Vec<String>>, std::io::Error> {
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let contents = tokio::fs::read_to_string(path).await;
+    contents
+        .and_then(|contents| WordList::parse(&contents))
+        .map(Some)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+/// Writes `report` as one line of JSON.
+fn print_json(report: &Report) -> Result<(), std::io::Error> {
+    println!("{}", serde_json::to_string(report)?);
+    Ok(())
+}
+
+/// Prints why a guess was rejected or a command could not go on; an `error` report in JSON.
+fn print_error(config: &Args, message: String) -> Result<(), std::io::Error> {
+    match config.format {
+        Format::Text => println!("{}", message),
+        Format::Json => print_json(&Report::error(message))?,
     }
-    writeln!(&mut stdout, "")?;
     Ok(())
 }
@@ -0,0 +1,189 @@
+//! Games of several boards at once, such as Dordle and Quordle.
+
+use crate::game::{CheckData, Game, GuessResult};
+use crate::word_list::WordList;
+
+/// Several boards played at once with one shared stream of guesses, as in Dordle (2 boards),
+/// Quordle (4) and Octordle (8). A board stops taking guesses once it is solved.
+#[derive(Clone, Debug)]
+pub struct MultiGame {
+    boards: Vec<Game>,
+    day: Option<usize>,
+    max_guesses: usize,
+}
+
+impl MultiGame {
+    /// One board for each of `words`, with the default number of guesses for that many boards.
+    pub fn for_words(words: &[&str]) -> Self {
+        let max_guesses = MultiGame::default_max_guesses(words.len());
+        MultiGame {
+            boards: words
+                .iter()
+                .map(|w| Game::for_word(w).set_max_guesses(max_guesses))
+                .collect(),
+            day: None,
+            max_guesses,
+        }
+    }
+
+    /// The boards for a day are consecutive words from the answer list, so each day and board
+    /// count gets its own set of words.
+    pub fn new(day: Option<usize>, boards: usize) -> Self {
+        let day = Game::puzzle_day(day);
+        let answers = WordList::contest().word_count().max(1);
+        let words = (0..boards)
+            .map(|i| {
+                let index = day.unwrap_or(0) * boards + i;
+                WordList::get_word_for_day(index % answers).unwrap()
+            })
+            .collect::<Vec<&str>>();
+
+        MultiGame {
+            day,
+            ..MultiGame::for_words(&words)
+        }
+    }
+
+    /// Applies hard mode to every board.
+    pub fn set_hard_mode(self) -> Self {
+        self.map_boards(Game::set_hard_mode)
+    }
+
+    /// Limits the guesses accepted on every board to `word_list`.
+    pub fn set_word_list(self, word_list: WordList) -> Self {
+        self.map_boards(|board| board.set_word_list(word_list.clone()))
+    }
+
+    /// Ends the game in a loss after `max_guesses` guesses.
+    pub fn set_max_guesses(self, max_guesses: usize) -> Self {
+        MultiGame {
+            max_guesses,
+            ..self.map_boards(|board| board.set_max_guesses(max_guesses))
+        }
+    }
+
+    fn map_boards(self, f: impl Fn(Game) -> Game) -> Self {
+        MultiGame {
+            boards: self.boards.into_iter().map(f).collect(),
+            ..self
+        }
+    }
+
+    /// 7 guesses for 2 boards, 9 for 4 and 13 for 8.
+    pub fn default_max_guesses(boards: usize) -> usize {
+        boards + 5
+    }
+
+    /// The boards, in the order their words were given.
+    pub fn boards(&self) -> &[Game] {
+        &self.boards
+    }
+
+    /// The puzzle number, if this is a daily puzzle.
+    pub fn day(&self) -> Option<usize> {
+        self.day
+    }
+
+    /// The number of guesses allowed.
+    pub fn max_guesses(&self) -> usize {
+        self.max_guesses
+    }
+
+    /// Number of letters in each secret word.
+    pub fn word_length(&self) -> usize {
+        self.boards.first().map_or(0, |b| b.word_length())
+    }
+
+    /// `Win` once every board is solved, `Lose` once the guesses run out first.
+    pub fn result(&self) -> GuessResult {
+        if self.boards.iter().all(is_solved) {
+            GuessResult::Win
+        } else if self
+            .boards
+            .iter()
+            .any(|b| matches!(b.result(), Some(GuessResult::Lose)))
+        {
+            GuessResult::Lose
+        } else {
+            GuessResult::Incorrect
+        }
+    }
+
+    /// Plays `guess` on every unsolved board. The rows line up with the boards; solved boards get
+    /// `None`. A word that is not in the list, or breaks hard mode on any board, is rejected before
+    /// any board counts it.
+    pub fn check(&mut self, guess: &str) -> (GuessResult, Vec<Option<CheckData>>) {
+        let mut rows = vec![None; self.boards.len()];
+        if !matches!(self.result(), GuessResult::Incorrect) {
+            return (self.result(), rows);
+        }
+
+        let mut boards = self.boards.clone();
+        for (board, row) in boards.iter_mut().zip(rows.iter_mut()) {
+            if is_solved(board) {
+                continue;
+            }
+
+            let result = board.check(guess);
+            if let GuessResult::Unknown(_) | GuessResult::Invalid(_) = result.result {
+                return (result.result, vec![None; self.boards.len()]);
+            }
+            *row = Some(result);
+        }
+
+        self.boards = boards;
+        (self.result(), rows)
+    }
+}
+
+fn is_solved(board: &Game) -> bool {
+    matches!(board.result(), Some(GuessResult::Win))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solved_boards_stop_taking_guesses() {
+        let mut game = MultiGame::for_words(&["crane", "slate"]);
+        assert_eq!(game.max_guesses(), 7);
+
+        let (result, rows) = game.check("crane");
+        assert!(matches!(result, GuessResult::Incorrect));
+        assert!(matches!(rows[0].as_ref().unwrap().result, GuessResult::Win));
+        assert!(matches!(
+            rows[1].as_ref().unwrap().result,
+            GuessResult::Incorrect
+        ));
+
+        let (result, rows) = game.check("slate");
+        assert!(rows[0].is_none());
+        assert!(matches!(result, GuessResult::Win));
+        assert_eq!(game.boards()[0].guesses().len(), 1);
+        assert_eq!(game.boards()[1].guesses().len(), 2);
+    }
+
+    #[test]
+    fn lose_when_guesses_run_out() {
+        let mut game = MultiGame::for_words(&["crane", "slate"]).set_max_guesses(2);
+        game.check("crane");
+        let (result, _) = game.check("pious");
+        assert!(matches!(result, GuessResult::Lose));
+        assert!(matches!(game.check("slate").0, GuessResult::Lose));
+    }
+
+    #[test]
+    fn rejected_guesses_are_not_counted_on_any_board() {
+        let mut game = MultiGame::for_words(&["crane", "slate"]);
+        assert!(matches!(game.check("zzzzz").0, GuessResult::Unknown(_)));
+
+        // "state" keeps everything "trace" revealed on "slate" but drops the green r of "crane".
+        let mut game = game.set_hard_mode();
+        game.check("trace");
+        let (result, rows) = game.check("state");
+        assert!(matches!(result, GuessResult::Invalid(_)));
+        assert!(rows.iter().all(Option::is_none));
+        assert!(game.boards().iter().all(|b| b.guesses().len() == 1));
+    }
+}
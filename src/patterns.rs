@@ -0,0 +1,221 @@
+//! The feedback pattern of every guess against every answer, computed once and cached on disk.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::game::{pattern, pattern_count};
+use crate::word_list::{hash_lists, WordList};
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+static SHARED: OnceLock<Option<PatternMatrix>> = OnceLock::new();
+
+const MAGIC: &[u8; 8] = b"WLPTRN01";
+/// Magic, hash of the word lists, then the number of rows and of columns.
+const HEADER: usize = 8 + 8 + 4 + 4;
+
+enum Cells {
+    Built(Vec<u8>),
+    Mapped(Mmap),
+}
+
+/// The feedback pattern (see `game::pattern`) for every guess against every answer, one byte per
+/// pair with a row per guess. Only words of five letters or fewer fit in a byte.
+pub struct PatternMatrix {
+    guesses: HashMap<&'static str, usize>,
+    answers: HashMap<&'static str, usize>,
+    cells: Cells,
+}
+
+impl PatternMatrix {
+    /// The matrix for `WordList::new()` against `WordList::contest()`, read from the cache file if
+    /// it was built for the same lists and written there otherwise. `None` if the words are too
+    /// long for the matrix.
+    pub fn shared() -> Option<&'static PatternMatrix> {
+        SHARED
+            .get_or_init(|| {
+                let mut guesses = WordList::new().get();
+                guesses.sort_unstable();
+                let mut answers = WordList::contest().get();
+                answers.sort_unstable();
+                match cache_path() {
+                    // A cache that cannot be written is only a missed speedup.
+                    Some(path) => PatternMatrix::load_or_build(&path, &guesses, &answers)
+                        .unwrap_or_else(|_| PatternMatrix::build(&guesses, &answers)),
+                    None => PatternMatrix::build(&guesses, &answers),
+                }
+            })
+            .as_ref()
+    }
+
+    /// Computes every pattern, a row per guess in parallel.
+    pub fn build(guesses: &[&'static str], answers: &[&'static str]) -> Option<Self> {
+        let length = answers.first()?.len();
+        if pattern_count(length) > 256
+            || guesses
+                .iter()
+                .chain(answers.iter())
+                .any(|w| w.len() != length)
+        {
+            return None;
+        }
+
+        let mut cells = vec![0_u8; guesses.len() * answers.len()];
+        cells
+            .par_chunks_mut(answers.len())
+            .zip(guesses.par_iter())
+            .for_each(|(row, guess)| {
+                for (cell, answer) in row.iter_mut().zip(answers.iter()) {
+                    *cell = pattern(guess, answer) as u8;
+                }
+            });
+        Some(PatternMatrix::new(guesses, answers, Cells::Built(cells)))
+    }
+
+    /// Maps the matrix saved at `path` if it was built from the same lists, otherwise builds it
+    /// and replaces the file.
+    pub fn load_or_build(
+        path: &Path,
+        guesses: &[&'static str],
+        answers: &[&'static str],
+    ) -> Result<Option<Self>, std::io::Error> {
+        let hash = hash_lists(guesses.iter().copied(), answers.iter().copied());
+        // Safety: the cache is only ever replaced by renaming a new file over it, never written in
+        // place, so the mapped contents do not change underneath us.
+        if let Some(map) = File::open(path)
+            .ok()
+            .and_then(|file| unsafe { Mmap::map(&file) }.ok())
+        {
+            if header(hash, guesses.len(), answers.len()) == map[..HEADER.min(map.len())]
+                && map.len() == HEADER + guesses.len() * answers.len()
+            {
+                return Ok(Some(PatternMatrix::new(
+                    guesses,
+                    answers,
+                    Cells::Mapped(map),
+                )));
+            }
+        }
+
+        let matrix = match PatternMatrix::build(guesses, answers) {
+            Some(matrix) => matrix,
+            None => return Ok(None),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Each process writes its own file, so two building at once can't interleave their writes.
+        let partial = path.with_extension(format!("partial.{}", std::process::id()));
+        let written = File::create(&partial).and_then(|mut file| {
+            file.write_all(&header(hash, guesses.len(), answers.len()))?;
+            file.write_all(matrix.cells())?;
+            std::fs::rename(&partial, path)
+        });
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e);
+        }
+        Ok(Some(matrix))
+    }
+
+    fn new(guesses: &[&'static str], answers: &[&'static str], cells: Cells) -> Self {
+        let index = |words: &[&'static str]| {
+            words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| (*w, i))
+                .collect::<HashMap<&'static str, usize>>()
+        };
+        PatternMatrix {
+            guesses: index(guesses),
+            answers: index(answers),
+            cells,
+        }
+    }
+
+    fn cells(&self) -> &[u8] {
+        match &self.cells {
+            Cells::Built(cells) => cells,
+            Cells::Mapped(map) => &map[HEADER..],
+        }
+    }
+
+    /// The row of `guess`, if it is in the matrix.
+    pub fn guess_index(&self, guess: &str) -> Option<usize> {
+        self.guesses.get(guess).copied()
+    }
+
+    /// The column of `answer`, if it is in the matrix.
+    pub fn answer_index(&self, answer: &str) -> Option<usize> {
+        self.answers.get(answer).copied()
+    }
+
+    /// The patterns of one guess against every answer, indexed by `answer_index`.
+    pub fn row(&self, guess: usize) -> &[u8] {
+        let columns = self.answers.len();
+        &self.cells()[guess * columns..(guess + 1) * columns]
+    }
+}
+
+/// `$XDG_CACHE_HOME/wordless/patterns.bin`, falling back to `~/.cache`. `$WORDLESS_CACHE_DIR`
+/// replaces the `wordless` directory; set to an empty string, nothing is cached.
+fn cache_path() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("WORDLESS_CACHE_DIR") {
+        return Some(PathBuf::from(dir))
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.join("patterns.bin"));
+    }
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .map(|cache| cache.join("wordless").join("patterns.bin"))
+}
+
+fn header(hash: u64, rows: usize, columns: usize) -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.extend_from_slice(&hash.to_le_bytes());
+    header.extend_from_slice(&(rows as u32).to_le_bytes());
+    header.extend_from_slice(&(columns as u32).to_le_bytes());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_matrix_matches_pattern() {
+        let guesses = ["crane", "eerie", "slate"];
+        let answers = ["geese", "plate"];
+        let path = std::env::temp_dir().join(format!("wordless-{}.bin", std::process::id()));
+
+        let built = PatternMatrix::load_or_build(&path, &guesses, &answers)
+            .unwrap()
+            .unwrap();
+        let mapped = PatternMatrix::load_or_build(&path, &guesses, &answers)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(mapped.cells, Cells::Mapped(_)));
+        for matrix in [built, mapped] {
+            for guess in guesses {
+                let row = matrix.row(matrix.guess_index(guess).unwrap());
+                for answer in answers {
+                    let cell = row[matrix.answer_index(answer).unwrap()];
+                    assert_eq!(u32::from(cell), pattern(guess, answer));
+                }
+            }
+        }
+
+        // A different list invalidates the cache.
+        let rebuilt = PatternMatrix::load_or_build(&path, &guesses[..2], &answers)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(rebuilt.cells, Cells::Built(_)));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,348 @@
+//! The structured output of `--format json`, one report per line.
+
+use std::collections::BTreeMap;
+
+use crate::bench::BenchReport;
+use crate::feedback::Mark;
+use crate::game::{Game, GuessResult, LetterResult};
+use crate::history::Stats;
+use crate::multi::MultiGame;
+use crate::suggest::Suggestion;
+use crate::tree::DecisionTree;
+use serde::Serialize;
+
+/// One line of `--format json` output: a JSON object whose `type` field names the variant, in
+/// snake case. Fields may be added over time but are never renamed or removed.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Report {
+    /// A guess and its colors.
+    Guess(Row),
+    /// A guess on every board of a multi-board game; `null` for boards already solved.
+    MultiGuess {
+        /// One row per board.
+        boards: Vec<Option<Row>>,
+    },
+    /// A finished game.
+    Result(GameResult),
+    /// A finished multi-board game.
+    MultiResult(MultiResult),
+    /// The best next guesses, best first.
+    Suggestions {
+        /// Possible answers left.
+        remaining: usize,
+        /// The suggestions asked for.
+        suggestions: Vec<SuggestionRow>,
+    },
+    /// The next guess looked up in a decision tree.
+    TreeGuess {
+        /// Possible answers left.
+        remaining: usize,
+        /// The guess to make.
+        word: String,
+    },
+    /// Totals from the history file.
+    Stats(StatsReport),
+    /// How the solver did over the puzzle answers.
+    Bench(BenchSummary),
+    /// A decision tree that was built and saved.
+    Tree(TreeSummary),
+    /// Answers consistent with a share grid.
+    PossibleAnswers {
+        /// The answers, in alphabetical order.
+        answers: Vec<String>,
+    },
+    /// Guesses that could have produced one row of a share grid for a known answer.
+    RowGuesses {
+        /// The row.
+        marks: Vec<Mark>,
+        /// The guesses, in alphabetical order.
+        guesses: Vec<String>,
+    },
+    /// Anything that stopped the command or rejected a guess.
+    Error {
+        /// What went wrong, as the text output would say it.
+        message: String,
+    },
+}
+
+/// A guess and the color of each of its letters.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Row {
+    /// The guessed word.
+    pub guess: String,
+    /// Each letter of the guess, in order.
+    pub letters: Vec<Letter>,
+}
+
+/// One letter of a guess.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Letter {
+    /// The letter.
+    pub letter: char,
+    /// `green`, `yellow` or `grey`.
+    pub state: Mark,
+}
+
+/// A finished game.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GameResult {
+    /// `wordle` or `absurdle`.
+    pub game: String,
+    /// The puzzle number, or `null` for a game that is not a daily puzzle.
+    pub day: Option<usize>,
+    /// The secret word.
+    pub word: String,
+    /// Whether the game was won.
+    pub won: bool,
+    /// Number of guesses made.
+    pub guesses: usize,
+    /// `null` when there is no limit, as in Absurdle.
+    pub max_guesses: Option<usize>,
+    /// Whether hard mode was on.
+    pub hard: bool,
+    /// Whether suggestions were shown during the game.
+    pub assisted: bool,
+    /// Every guess, oldest first.
+    pub rows: Vec<Row>,
+}
+
+/// A finished multi-board game.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MultiResult {
+    /// The puzzle number, or `null` for a game that is not a daily puzzle.
+    pub day: Option<usize>,
+    /// Number of guesses allowed.
+    pub max_guesses: usize,
+    /// Whether suggestions were shown during the game.
+    pub assisted: bool,
+    /// Each board, in order.
+    pub boards: Vec<BoardResult>,
+}
+
+/// One board of a finished multi-board game.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BoardResult {
+    /// The secret word of the board.
+    pub word: String,
+    /// Whether the board was solved.
+    pub won: bool,
+    /// Guesses the board took, counting the one that solved it.
+    pub guesses: usize,
+}
+
+/// One suggested guess.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SuggestionRow {
+    /// The guess.
+    pub word: String,
+    /// Words left in the worst case.
+    pub remaining: usize,
+    /// Exact-position matches with the words in play, used to break ties.
+    pub score: i64,
+    /// Expected information from the guess, in bits.
+    pub entropy: f64,
+    /// Occurrences of the word in the bundled corpus.
+    pub frequency: u64,
+}
+
+/// Totals over the games in the history file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StatsReport {
+    /// Games finished.
+    pub played: usize,
+    /// Games won.
+    pub wins: usize,
+    /// Share of games won, rounded down.
+    pub win_percent: usize,
+    /// Wins on consecutive puzzle days, up to the latest puzzle played.
+    pub current_streak: usize,
+    /// Longest run of wins on consecutive puzzle days.
+    pub max_streak: usize,
+    /// Games won with each guess count, keyed by the count.
+    pub distribution: BTreeMap<usize, usize>,
+}
+
+/// How the solver did over the puzzle answers.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BenchSummary {
+    /// Games played.
+    pub games: usize,
+    /// Average guesses over the games that were won.
+    pub mean: f64,
+    /// Answers the solver ran out of guesses on.
+    pub failures: Vec<String>,
+    /// Games won with each guess count, keyed by the count.
+    pub distribution: BTreeMap<usize, usize>,
+    /// Seconds the benchmark took.
+    pub seconds: f64,
+}
+
+/// A decision tree that was built and saved.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TreeSummary {
+    /// The first guess of the tree.
+    pub opening: String,
+    /// Number of answers the tree covers.
+    pub answers: usize,
+    /// Average guesses per answer.
+    pub mean: f64,
+    /// Answers not solved within the guess limit.
+    pub failures: usize,
+    /// Seconds the build took.
+    pub seconds: f64,
+}
+
+impl Row {
+    /// The row for the feedback on one guess.
+    pub fn new(letters: &[LetterResult]) -> Self {
+        Row {
+            guess: letters.iter().map(|l| l.to_char()).collect(),
+            letters: letters
+                .iter()
+                .map(|l| Letter {
+                    letter: l.to_char(),
+                    state: Mark::from(l),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Report {
+    /// Reports the feedback on one guess.
+    pub fn guess(letters: &[LetterResult]) -> Self {
+        Report::Guess(Row::new(letters))
+    }
+
+    /// Returns `None` while the game is still in progress.
+    pub fn result(game: &Game, assisted: bool) -> Option<Self> {
+        let won = match game.result()? {
+            GuessResult::Win => true,
+            GuessResult::Lose => false,
+            _ => return None,
+        };
+        let guesses = game.guesses();
+        Some(Report::Result(GameResult {
+            game: if game.is_adversarial() {
+                "absurdle"
+            } else {
+                "wordle"
+            }
+            .to_string(),
+            day: game.day(),
+            word: game.word(),
+            won,
+            guesses: guesses.len(),
+            max_guesses: game.max_guesses(),
+            hard: !game.is_easy(),
+            assisted,
+            rows: guesses.iter().map(|g| Row::new(&g.letters)).collect(),
+        }))
+    }
+
+    /// Reports a finished multi-board game.
+    pub fn multi_result(game: &MultiGame, assisted: bool) -> Self {
+        Report::MultiResult(MultiResult {
+            day: game.day(),
+            max_guesses: game.max_guesses(),
+            assisted,
+            boards: game
+                .boards()
+                .iter()
+                .map(|board| BoardResult {
+                    word: board.word(),
+                    won: matches!(board.result(), Some(GuessResult::Win)),
+                    guesses: board.guesses().len(),
+                })
+                .collect(),
+        })
+    }
+
+    /// The first `count` of `suggestions`.
+    pub fn suggestions(remaining: usize, suggestions: &[Suggestion], count: usize) -> Self {
+        Report::Suggestions {
+            remaining,
+            suggestions: suggestions
+                .iter()
+                .take(count)
+                .map(|s| SuggestionRow {
+                    word: s.word.to_string(),
+                    remaining: s.remaining,
+                    score: s.score,
+                    entropy: s.entropy,
+                    frequency: s.frequency,
+                })
+                .collect(),
+        }
+    }
+
+    /// Reports the totals from the history file.
+    pub fn stats(stats: &Stats) -> Self {
+        Report::Stats(StatsReport {
+            played: stats.played,
+            wins: stats.wins,
+            win_percent: stats.win_percent(),
+            current_streak: stats.current_streak,
+            max_streak: stats.max_streak,
+            distribution: stats.distribution.clone(),
+        })
+    }
+
+    /// Reports a benchmark that took `seconds`.
+    pub fn bench(report: &BenchReport, seconds: f64) -> Self {
+        Report::Bench(BenchSummary {
+            games: report.games(),
+            mean: report.mean(),
+            failures: report.failures.iter().map(|w| w.to_string()).collect(),
+            distribution: report.histogram.clone(),
+            seconds,
+        })
+    }
+
+    /// Reports a decision tree that took `seconds` to build.
+    pub fn tree(tree: &DecisionTree, seconds: f64) -> Self {
+        Report::Tree(TreeSummary {
+            opening: tree.root.guess.clone(),
+            answers: tree.answers,
+            mean: tree.mean(),
+            failures: tree.failures,
+            seconds,
+        })
+    }
+
+    /// Reports an error.
+    pub fn error(message: impl Into<String>) -> Self {
+        Report::Error {
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_schema_is_stable() {
+        let mut game = Game::for_word("crane");
+        let row = game.check("react");
+        assert_eq!(
+            serde_json::to_string(&Report::guess(&row.letters)).unwrap(),
+            concat!(
+                r#"{"type":"guess","guess":"react","letters":["#,
+                r#"{"letter":"r","state":"yellow"},{"letter":"e","state":"yellow"},"#,
+                r#"{"letter":"a","state":"green"},{"letter":"c","state":"yellow"},"#,
+                r#"{"letter":"t","state":"grey"}]}"#
+            )
+        );
+
+        game.check("crane");
+        let result = serde_json::to_value(Report::result(&game, false).unwrap()).unwrap();
+        assert_eq!(result["type"], "result");
+        assert_eq!(result["game"], "wordle");
+        assert_eq!(result["won"], true);
+        assert_eq!(result["guesses"], 2);
+        assert_eq!(result["rows"][1]["guess"], "crane");
+    }
+}
@@ -0,0 +1,190 @@
+//! Working back from a shared emoji grid to the answers and guesses behind it.
+
+use std::fmt;
+
+use crate::feedback::{pattern_of, Mark};
+use crate::game::pattern;
+use crate::patterns::PatternMatrix;
+use rayon::prelude::*;
+
+/// Why the guesses for a share grid could not be worked out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReverseError {
+    /// The answer is not a word of the guesses' length made of the letters a to z.
+    InvalidAnswer {
+        /// The answer given.
+        answer: String,
+        /// The length of the guesses.
+        length: usize,
+    },
+    /// A row has a different number of marks than the answer has letters.
+    LengthMismatch {
+        /// The answer given.
+        answer: String,
+        /// Number of marks in the row.
+        marks: usize,
+    },
+    /// A row has a different number of marks than the possible answers have letters.
+    RowLength {
+        /// The length of the answers.
+        length: usize,
+        /// Number of marks in the row.
+        marks: usize,
+    },
+}
+
+impl fmt::Display for ReverseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReverseError::InvalidAnswer { answer, length } => {
+                write!(f, "'{}' is not a {}-letter word", answer, length)
+            }
+            ReverseError::LengthMismatch { answer, marks } => write!(
+                f,
+                "'{}' has {} letters but a row has {} marks",
+                answer,
+                answer.chars().count(),
+                marks
+            ),
+            ReverseError::RowLength { length, marks } => write!(
+                f,
+                "the answers have {} letters but a row has {} marks",
+                length, marks
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReverseError {}
+
+/// Answers for which every row of `grid` could have been produced by some word in `guesses`.
+/// Nothing is assumed about the order of the rows, so hard mode is not taken into account.
+pub fn possible_answers(
+    grid: &[Vec<Mark>],
+    answers: &[&'static str],
+    guesses: &[&'static str],
+) -> Result<Vec<&'static str>, ReverseError> {
+    let length = answers.first().map_or(0, |a| a.len());
+    if let Some(row) = grid.iter().find(|row| row.len() != length) {
+        return Err(ReverseError::RowLength {
+            length,
+            marks: row.len(),
+        });
+    }
+
+    let rows = grid.iter().map(|row| pattern_of(row)).collect::<Vec<u32>>();
+    let matrix = PatternMatrix::shared();
+    let guess_rows = guesses
+        .iter()
+        .map(|guess| matrix.and_then(|m| m.guess_index(guess).map(|i| m.row(i))))
+        .collect::<Vec<Option<&[u8]>>>();
+    let mut possible = answers
+        .par_iter()
+        .filter(|answer| {
+            let column = matrix.and_then(|m| m.answer_index(answer));
+            let feedback = |i: usize| match (guess_rows[i], column) {
+                (Some(row), Some(column)) => u32::from(row[column]),
+                _ => pattern(guesses[i], answer),
+            };
+            rows.iter().all(|row| {
+                (0..guesses.len()).any(|i| guesses[i].len() == answer.len() && feedback(i) == *row)
+            })
+        })
+        .copied()
+        .collect::<Vec<&'static str>>();
+    possible.sort_unstable();
+    Ok(possible)
+}
+
+/// For each row of `grid`, the words in `guesses` that would have produced it against `answer`.
+pub fn guesses_for_rows(
+    grid: &[Vec<Mark>],
+    answer: &str,
+    guesses: &[&'static str],
+) -> Result<Vec<Vec<&'static str>>, ReverseError> {
+    let length = guesses.first().map_or(answer.len(), |g| g.len());
+    if answer.len() != length || !answer.chars().all(|c| c.is_ascii_lowercase()) {
+        return Err(ReverseError::InvalidAnswer {
+            answer: answer.to_string(),
+            length,
+        });
+    }
+    if let Some(row) = grid.iter().find(|row| row.len() != answer.len()) {
+        return Err(ReverseError::LengthMismatch {
+            answer: answer.to_string(),
+            marks: row.len(),
+        });
+    }
+
+    Ok(grid
+        .iter()
+        .map(|row| {
+            let row = pattern_of(row);
+            let mut words = guesses
+                .iter()
+                .filter(|guess| guess.len() == answer.len() && pattern(guess, answer) == row)
+                .copied()
+                .collect::<Vec<&'static str>>();
+            words.sort_unstable();
+            words
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feedback::parse_marks;
+
+    const GUESSES: [&str; 5] = ["crane", "crate", "grace", "slate", "trace"];
+
+    #[test]
+    fn rows_are_matched_to_guesses() {
+        let grid = ["🟩🟩🟩⬛🟩", "⬛⬛🟩⬛🟩"]
+            .iter()
+            .map(|row| parse_marks(row).unwrap())
+            .collect::<Vec<Vec<Mark>>>();
+        assert_eq!(
+            guesses_for_rows(&grid, "crane", &GUESSES).unwrap(),
+            vec![vec!["crate"], vec!["slate"]]
+        );
+        assert_eq!(
+            possible_answers(&grid, &GUESSES, &GUESSES).unwrap(),
+            vec!["crane"]
+        );
+    }
+
+    #[test]
+    fn bad_answers_are_rejected() {
+        let grid = vec![parse_marks("🟩🟩🟩🟩🟩").unwrap()];
+        assert_eq!(
+            guesses_for_rows(&grid, "cr4ne", &GUESSES).unwrap_err(),
+            ReverseError::InvalidAnswer {
+                answer: "cr4ne".to_string(),
+                length: 5
+            }
+        );
+        assert!(guesses_for_rows(&grid, "CRANE", &GUESSES).is_err());
+        assert!(guesses_for_rows(&grid, "cranes", &GUESSES).is_err());
+        assert_eq!(
+            guesses_for_rows(&[parse_marks("🟩🟩🟩🟩").unwrap()], "crane", &GUESSES)
+                .unwrap_err()
+                .to_string(),
+            "'crane' has 5 letters but a row has 4 marks"
+        );
+    }
+
+    #[test]
+    fn rows_of_the_wrong_length_are_rejected() {
+        for row in ["🟩🟩🟩🟩", "⬛🟩🟩🟩🟩🟩"] {
+            let grid = vec![parse_marks(row).unwrap()];
+            assert_eq!(
+                possible_answers(&grid, &GUESSES, &GUESSES).unwrap_err(),
+                ReverseError::RowLength {
+                    length: 5,
+                    marks: grid[0].len()
+                }
+            );
+        }
+    }
+}
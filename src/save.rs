@@ -0,0 +1,36 @@
+//! Games in progress, kept between runs so a puzzle can be picked up where it was left.
+
+use std::path::{Path, PathBuf};
+
+use crate::game::Game;
+use crate::history::data_dir;
+use crate::word_list::WordList;
+
+/// Where the game for puzzle `day` is kept between runs. Puzzle numbers only mean something for
+/// one pair of word lists, so the name includes a hash of the lists in use.
+pub fn path_for_day(day: usize) -> Option<PathBuf> {
+    data_dir().map(|dir| {
+        dir.join("games")
+            .join(format!("{}-{:016x}.json", day, WordList::lists_hash()))
+    })
+}
+
+/// Reads a saved game; `None` if there is nothing saved at `path`, or if what is saved there is
+/// not the puzzle for its day with the word lists in use.
+pub async fn load(path: &Path) -> Result<Option<Game>, std::io::Error> {
+    let game: Game = match tokio::fs::read_to_string(path).await {
+        Ok(contents) => serde_json::from_str(&contents)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let word = game.day().and_then(WordList::get_word_for_day);
+    Ok(Some(game).filter(|game| word == Some(game.word().as_str())))
+}
+
+/// Overwrites whatever was saved at `path` with the current state of `game`.
+pub async fn store(path: &Path, game: &Game) -> Result<(), std::io::Error> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(path, serde_json::to_string(game)?).await
+}
@@ -0,0 +1,379 @@
+use std::{
+    io::Stdout,
+    path::Path,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use itertools::Itertools;
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame, Terminal,
+};
+use wordless::constraints::eliminate_words;
+use wordless::feedback::Mark;
+use wordless::game::{Game, GuessResult};
+use wordless::save;
+use wordless::suggest::{suggest, AnswerPool, Ranking, SuggestError, Suggestion};
+use wordless::word_list::WordList;
+
+/// How often the screen is redrawn while waiting for a key, which is also the speed of the shake.
+const TICK: Duration = Duration::from_millis(50);
+const SHAKE_TICKS: usize = 8;
+
+/// Settings for the suggestion side panel; the panel is hidden without them.
+#[derive(Clone, Copy, Debug)]
+pub struct SuggestPanel {
+    pub count: usize,
+    pub easy: bool,
+    pub ranking: Ranking,
+    pub pool: AnswerPool,
+}
+
+struct App<'a> {
+    game: Game,
+    input: String,
+    message: Option<String>,
+    /// Ticks left in the shake of the row being typed after a rejected guess.
+    shake: usize,
+    panel: Option<SuggestPanel>,
+    remaining: usize,
+    suggestions: Option<Result<Vec<Suggestion>, String>>,
+    pending: Option<Receiver<Result<Vec<Suggestion>, SuggestError>>>,
+    save_path: Option<&'a Path>,
+}
+
+/// Plays `game` full screen until it is finished or the player quits with Esc, saving after every
+/// guess if `save_path` is given. Returns the game as it was left.
+pub async fn run(
+    game: Game,
+    panel: Option<SuggestPanel>,
+    save_path: Option<&Path>,
+) -> Result<Game, std::io::Error> {
+    let mut stdout = std::io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let mut app = App {
+        game,
+        input: String::new(),
+        message: None,
+        shake: 0,
+        panel,
+        remaining: 0,
+        suggestions: None,
+        pending: None,
+        save_path,
+    };
+    app.refresh_suggestions();
+    let result = app.run(&mut terminal).await;
+
+    terminal::disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result.map(|_| app.game)
+}
+
+impl<'a> App<'a> {
+    async fn run(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    ) -> Result<(), std::io::Error> {
+        loop {
+            self.receive_suggestions();
+            terminal.draw(|f| self.draw(f))?;
+            self.shake = self.shake.saturating_sub(1);
+
+            if !event::poll(TICK)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                if !self.handle_key(key).await? {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns `false` once the player is done.
+    async fn handle_key(&mut self, key: KeyEvent) -> Result<bool, std::io::Error> {
+        let over = matches!(
+            self.game.result(),
+            Some(GuessResult::Win | GuessResult::Lose)
+        );
+        match key.code {
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(false)
+            }
+            _ if over => return Ok(false),
+            KeyCode::Char(c)
+                if c.is_ascii_alphabetic() && self.input.len() < self.game.word_length() =>
+            {
+                self.input.push(c.to_ascii_lowercase());
+                self.message = None;
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.message = None;
+            }
+            KeyCode::Enter => self.submit().await?,
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    async fn submit(&mut self) -> Result<(), std::io::Error> {
+        if self.input.len() != self.game.word_length() {
+            self.reject("Not enough letters".to_string());
+            return Ok(());
+        }
+
+        let result = self.game.check(&self.input);
+        match result.result {
+            GuessResult::Unknown(_) => {
+                self.reject("Not in word list".to_string());
+                return Ok(());
+            }
+            GuessResult::Invalid(e) => {
+                self.reject(format!("Hard mode: {}", e));
+                return Ok(());
+            }
+            GuessResult::Win => self.message = Some("Splendid!".to_string()),
+            GuessResult::Lose => {
+                self.message = Some(self.game.word().to_ascii_uppercase());
+            }
+            GuessResult::Incorrect => self.refresh_suggestions(),
+        }
+
+        self.input.clear();
+        if let Some(path) = self.save_path {
+            save::store(path, &self.game).await?;
+        }
+        Ok(())
+    }
+
+    fn reject(&mut self, message: String) {
+        self.message = Some(message);
+        self.shake = SHAKE_TICKS;
+    }
+
+    /// Starts working out suggestions for the current position. They can take seconds early in
+    /// the game, so they are computed on another thread and picked up by `receive_suggestions`.
+    fn refresh_suggestions(&mut self) {
+        let panel = match self.panel {
+            Some(panel) => panel,
+            None => return,
+        };
+        let word_list = self
+            .game
+            .guesses()
+            .into_iter()
+            .fold(WordList::new(), |word_list, g| {
+                eliminate_words(word_list, g.letters)
+            });
+        let answers = panel.pool.answers(&word_list);
+        self.remaining = answers.word_count();
+        self.suggestions = None;
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let suggestions = suggest(word_list, panel.easy, panel.ranking, panel.pool);
+            // The receiver is gone if another guess was made in the meantime.
+            let _ = sender.send(suggestions);
+        });
+        self.pending = Some(receiver);
+    }
+
+    fn receive_suggestions(&mut self) {
+        if let Some(receiver) = &self.pending {
+            match receiver.try_recv() {
+                Ok(suggestions) => {
+                    self.suggestions = Some(suggestions.map_err(|e| e.to_string()));
+                    self.pending = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(match self.panel {
+                Some(_) => vec![Constraint::Min(0), Constraint::Length(40)],
+                None => vec![Constraint::Min(0)],
+            })
+            .split(f.size());
+
+        let rows = self.rows() as u16 * 2;
+        let board = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(1),
+                Constraint::Length(rows),
+                Constraint::Length(2),
+                Constraint::Length(4),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(columns[0]);
+
+        f.render_widget(
+            Paragraph::new(Span::styled(
+                "WORDLESS",
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+            .alignment(Alignment::Center),
+            board[0],
+        );
+        // Without a guess limit the grid keeps growing, so keep the latest rows in view.
+        f.render_widget(
+            Paragraph::new(self.grid())
+                .alignment(Alignment::Center)
+                .scroll((rows.saturating_sub(board[1].height), 0)),
+            board[1],
+        );
+        f.render_widget(
+            Paragraph::new(Span::styled(
+                self.message.clone().unwrap_or_default(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+            .alignment(Alignment::Center),
+            board[2],
+        );
+        f.render_widget(
+            Paragraph::new(self.keyboard()).alignment(Alignment::Center),
+            board[3],
+        );
+        f.render_widget(
+            Paragraph::new(Span::styled(
+                "Type a guess, Enter to submit, Backspace to delete, Esc to quit",
+                Style::default().fg(Color::DarkGray),
+            ))
+            .alignment(Alignment::Center),
+            board[5],
+        );
+
+        if let Some(panel) = self.panel {
+            self.draw_panel(f, panel, columns[1]);
+        }
+    }
+
+    /// One row per allowed guess, or without a limit the guesses so far and the one being typed.
+    fn rows(&self) -> usize {
+        self.game
+            .max_guesses()
+            .unwrap_or_else(|| self.game.guesses().len() + 1)
+    }
+
+    fn grid(&self) -> Vec<Spans<'static>> {
+        let length = self.game.word_length();
+        let guesses = self.game.guesses();
+        (0..self.rows())
+            .flat_map(|row| {
+                let cells: Vec<Span> = match guesses.get(row) {
+                    Some(guess) => guess
+                        .letters
+                        .iter()
+                        .map(|letter| cell(letter.to_char(), Some(Mark::from(letter))))
+                        .collect(),
+                    None if row == guesses.len() => (0..length)
+                        .map(|i| cell(self.input.chars().nth(i).unwrap_or(' '), None))
+                        .collect(),
+                    None => (0..length).map(|_| cell(' ', None)).collect(),
+                };
+
+                // Rejected guesses rock the row being typed from side to side.
+                let (left, right) = match (row == guesses.len(), self.shake % 4) {
+                    (true, 1) => ("  ", ""),
+                    (true, 3) => ("", "  "),
+                    _ => (" ", " "),
+                };
+                let mut line = vec![Span::raw(left)];
+                line.extend(Itertools::intersperse(cells.into_iter(), Span::raw(" ")));
+                line.push(Span::raw(right));
+                vec![Spans::from(line), Spans::default()]
+            })
+            .collect()
+    }
+
+    fn keyboard(&self) -> Vec<Spans<'static>> {
+        let states = self.game.letter_states();
+        ["qwertyuiop", "asdfghjkl", "zxcvbnm"]
+            .iter()
+            .map(|row| {
+                Spans::from(
+                    row.chars()
+                        .map(|c| {
+                            Span::styled(
+                                format!(" {} ", c.to_ascii_uppercase()),
+                                mark_style(states.get(&c).copied()),
+                            )
+                        })
+                        .collect::<Vec<Span>>(),
+                )
+            })
+            .collect()
+    }
+
+    fn draw_panel<B: Backend>(&self, f: &mut Frame<B>, panel: SuggestPanel, area: Rect) {
+        let mut lines = vec![
+            Spans::from(format!("Words remaining: {}", self.remaining)),
+            Spans::default(),
+        ];
+        match &self.suggestions {
+            None => lines.push(Spans::from("Thinking...")),
+            Some(Err(e)) => lines.push(Spans::from(e.clone())),
+            Some(Ok(suggestions)) => {
+                lines.push(Spans::from(Span::styled(
+                    format!("{:<8}{:>10}{:>10}", "Word", "Remaining", "Entropy"),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+                lines.extend(suggestions.iter().take(panel.count).map(|s| {
+                    Spans::from(format!(
+                        "{:<8}{:>10}{:>10.3}",
+                        s.word, s.remaining, s.entropy
+                    ))
+                }));
+            }
+        }
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().borders(Borders::ALL).title("Suggestions")),
+            area,
+        );
+    }
+}
+
+fn cell(c: char, mark: Option<Mark>) -> Span<'static> {
+    Span::styled(
+        format!(" {} ", c.to_ascii_uppercase()),
+        mark_style(mark).add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Colors for a tile or key; letters without a mark yet are shown on light grey.
+fn mark_style(mark: Option<Mark>) -> Style {
+    match mark {
+        Some(Mark::Green) => Style::default().fg(Color::Black).bg(Color::Green),
+        Some(Mark::Yellow) => Style::default().fg(Color::Black).bg(Color::Yellow),
+        Some(Mark::Grey) => Style::default().fg(Color::White).bg(Color::DarkGray),
+        None => Style::default().fg(Color::Black).bg(Color::Gray),
+    }
+}
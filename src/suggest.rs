@@ -1,86 +1,485 @@
-use std::{convert::TryInto, iter::FromIterator};
+//! Ranking the next guess by how well it splits the words still in play.
 
-use crate::dictionary_set::DictionarySet;
-use hash_histogram::HashHistogram;
-use itertools::Itertools;
-use rayon::{slice::ParallelSliceMut, prelude::*};
+use std::{convert::TryInto, fmt};
+
+use crate::constraints::Constraints;
+use crate::dictionary_set::{DictionarySet, WordBits};
+use crate::frequency::Frequency;
+use crate::game::{pattern, pattern_count};
+use crate::patterns::PatternMatrix;
 use crate::word_list::WordList;
+use itertools::Itertools;
+use rayon::{prelude::*, slice::ParallelSliceMut};
+
+/// How suggestions are ordered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ranking {
+    /// Prefer the guess with the highest expected information
+    Entropy,
+    /// Prefer the guess whose largest feedback bucket is smallest
+    WorstCase,
+}
+
+/// Which words are treated as possible answers when rating guesses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnswerPool {
+    /// Only puzzle answers, all equally likely
+    Contest,
+    /// Every accepted guess, all equally likely
+    Full,
+    /// Every accepted guess, weighted by how common the word is
+    Weighted,
+}
+
+impl AnswerPool {
+    /// The words in `word_list` that could be the answer. A word list with no puzzle answers left
+    /// (say, a custom secret word) is used as it is rather than ruled out.
+    pub fn answers(self, word_list: &WordList) -> WordList {
+        match self {
+            AnswerPool::Contest => {
+                let contest = WordList::contest();
+                let answers = word_list.retain(|w| contest.contains(w));
+                if answers.word_count() == 0 {
+                    word_list.clone()
+                } else {
+                    answers
+                }
+            }
+            AnswerPool::Full | AnswerPool::Weighted => word_list.clone(),
+        }
+    }
+
+    fn weight(self, word: &str, frequency: &Frequency) -> f64 {
+        match self {
+            AnswerPool::Weighted => frequency.weight(word),
+            AnswerPool::Contest | AnswerPool::Full => 1.0,
+        }
+    }
+}
+
+/// A guess worth trying and how it would split the words still in play.
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+    /// The guess.
+    pub word: &'static str,
+    /// Words left in the worst case.
+    pub remaining: usize,
+    /// Expected information from the guess, in bits.
+    pub entropy: f64,
+    /// Occurrences of the word in the bundled corpus.
+    pub frequency: u64,
+    /// Exact-position matches with the words in play, used to break ties.
+    pub score: i64,
+}
+
+/// Why no guess could be suggested.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SuggestError {
+    /// The feedback given rules out every word, so there is nothing to suggest.
+    NoWordsLeft,
+}
+
+impl fmt::Display for SuggestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuggestError::NoWordsLeft => write!(f, "no words match the feedback"),
+        }
+    }
+}
+
+impl std::error::Error for SuggestError {}
 
-/// The algorithm here is to find out how well words bisect the problem space. For that we create a 5-bit
-/// value for each word in the list determining with that letter is contained in the guess. For speed we
-/// group words that have the same letters together (i.e. order of the letters is not important) and
-/// rate the candidate words by the smallest max bucket (i.e. worst-case performance). Algorithm is O(o^2).
+/// For every candidate guess we compute the feedback pattern (green/yellow/grey per position, same
+/// rules as `Game::check`) against every word still in play and bucket the words by pattern. Each
+/// bucket is the set of words that would remain after seeing that feedback, so the candidate is
+/// rated by the expected information of the split (entropy, in bits) and by the size of its largest
+/// bucket (worst-case words remaining). Algorithm is O(c * w) in candidates and remaining words.
 ///
-/// We also calculate a second value--position score--which indicates how many times a word has an
-/// exact-position match. The higher the score the more likely it is a guess will cut the working set
-/// dramatically.
+/// `word_list` holds every accepted guess that fits the feedback so far; `pool` picks which of
+/// them could be the answer. With the weighted pool not every word is equally likely: each is
+/// weighted by how common it is in the bundled corpus (see `Frequency::weight`) and the entropy is
+/// computed over the weighted buckets. Ties are broken toward the more common word.
+///
+/// In easy mode any accepted guess is a candidate; in hard mode only those in `word_list`.
+///
+/// We also calculate a third value--position score--which indicates how many times a word has an
+/// exact-position match. It is used to break ties; the higher the score the more likely it is a
+/// guess will cut the working set dramatically. It is counted in the shared index of every guess.
 pub fn suggest(
-    set: DictionarySet,
     word_list: WordList,
     easy: bool,
-) -> Result<Vec<(&'static str, usize, i64)>, std::io::Error> {
-    println!("Words remaining: {}", word_list.word_count());
-    let words = word_list.get();
+    ranking: Ranking,
+    pool: AnswerPool,
+) -> Result<Vec<Suggestion>, SuggestError> {
+    let words = pool.answers(&word_list).get();
+    let frequency = Frequency::bnc();
 
-    let remaining = word_list.word_count();
+    let remaining = words.len();
+    if remaining == 0 {
+        return Err(SuggestError::NoWordsLeft);
+    }
     if remaining == 1 {
-        return Ok(vec![(words.first().unwrap(), 1, 5)]);
+        return Ok(vec![Suggestion {
+            word: words.first().unwrap(),
+            remaining: 1,
+            entropy: 0.0,
+            frequency: frequency.count(words.first().unwrap()),
+            score: words.first().unwrap().len().try_into().unwrap(),
+        }]);
     }
 
-    let mut candidates = if easy {
+    let candidates = if easy {
         WordList::new().get()
     } else {
-        words.clone()
+        word_list.get()
+    };
+
+    let index = DictionarySet::shared();
+    let answers = index.bits_of(words.iter().copied());
+    let (weights, total) = weights(&words, frequency, pool);
+    let matrix = Matrix::for_words(&words);
+    let mut reduction = candidates
+        .par_iter()
+        .map(|candidate| {
+            let (remaining, entropy) = split(candidate, &words, &matrix, &weights, total);
+            Suggestion {
+                word: candidate,
+                remaining,
+                entropy,
+                frequency: frequency.count(candidate),
+                score: calculate_score(index, &answers, candidate),
+            }
+        })
+        .collect::<Vec<Suggestion>>();
+
+    sort(&mut reduction, ranking);
+    Ok(reduction)
+}
+
+/// Suggests one guess for several boards played at once. Feedback on each board is independent,
+/// so a guess is rated by the sum of its entropy on every board and the sum of its worst cases.
+/// A board that is down to its last word is worth finishing, so that word is suggested outright.
+///
+/// In easy mode any accepted guess is a candidate. In hard mode a guess must keep to what every
+/// unsolved board has revealed, given in `revealed` in the same order as `word_lists`.
+pub fn suggest_multi(
+    word_lists: Vec<WordList>,
+    revealed: &[Constraints],
+    easy: bool,
+    ranking: Ranking,
+    pool: AnswerPool,
+) -> Result<Vec<Suggestion>, SuggestError> {
+    let allowed = |word: &str| {
+        easy || word_lists
+            .iter()
+            .zip(revealed)
+            .filter(|(list, _)| list.word_count() > 0)
+            .all(|(_, known)| known.check_hard_mode(word).is_ok())
     };
-    candidates.par_sort_by_key(|word| pattern_from_word(*word));
+    let frequency = Frequency::bnc();
+    let index = DictionarySet::shared();
+    let boards = word_lists
+        .iter()
+        .filter(|list| list.word_count() > 0)
+        .map(|list| {
+            let words = pool.answers(list).get();
+            let (weights, total) = weights(&words, frequency, pool);
+            let matrix = Matrix::for_words(&words);
+            (
+                index.bits_of(words.iter().copied()),
+                words,
+                matrix,
+                weights,
+                total,
+            )
+        })
+        .collect::<Vec<_>>();
 
-    let grouped = candidates
+    if boards.is_empty() {
+        return Err(SuggestError::NoWordsLeft);
+    }
+    if let Some((_, words, ..)) = boards
         .iter()
-        .group_by(|word| pattern_from_word(*word))
+        .find(|(_, words, ..)| words.len() == 1 && allowed(words[0]))
+    {
+        return Ok(vec![Suggestion {
+            word: words[0],
+            remaining: 1,
+            entropy: 0.0,
+            frequency: frequency.count(words[0]),
+            score: words[0].len().try_into().unwrap(),
+        }]);
+    }
+
+    let candidates = WordList::new()
+        .get()
         .into_iter()
-        .map(|(key, group)| (key, group.cloned().collect_vec()))
-        .collect_vec();
+        .filter(|word| allowed(word))
+        .collect::<Vec<&'static str>>();
+    if candidates.is_empty() {
+        return Err(SuggestError::NoWordsLeft);
+    }
 
-    let mut reduction = grouped
+    let mut reduction = candidates
         .par_iter()
-        .flat_map(|(char_pattern, pattern_words)| {
-            let mut hist: HashHistogram<u8> = HashHistogram::new();
-            for word in words.iter() {
-                let bucket = char_pattern.chars().fold(0_u8, |acc, c| {
-                    (acc << 1) + if word.contains(c) { 1 } else { 0 }
-                });
-                hist.bump(&bucket);
-            }
-
-            let remaining = hist.iter().map(|(_, count)| *count).max().unwrap_or(0);
-            pattern_words
-                .into_iter()
-                .map(move |w| (*w, remaining))
-                .par_bridge()
+        .map(|candidate| {
+            boards.iter().fold(
+                Suggestion {
+                    word: candidate,
+                    remaining: 0,
+                    entropy: 0.0,
+                    frequency: frequency.count(candidate),
+                    score: 0,
+                },
+                |mut suggestion, (answers, words, matrix, weights, total)| {
+                    let (remaining, entropy) = split(candidate, words, matrix, weights, *total);
+                    suggestion.remaining += remaining;
+                    suggestion.entropy += entropy;
+                    suggestion.score += calculate_score(index, answers, candidate);
+                    suggestion
+                },
+            )
         })
-        .map(|(word, count)| (word, count, calculate_score(&set, word)))
-        .collect::<Vec<(&'static str, usize, i64)>>();
+        .collect::<Vec<Suggestion>>();
 
-    reduction.par_sort_by_key(|(word, count, score)| (*count, -*score, *word));
+    sort(&mut reduction, ranking);
     Ok(reduction)
 }
 
-fn calculate_score(dictionary: &DictionarySet, word: &'static str) -> i64 {
+fn weights(words: &[&'static str], frequency: &Frequency, pool: AnswerPool) -> (Vec<f64>, f64) {
+    let weights = words
+        .iter()
+        .map(|word| pool.weight(word, frequency))
+        .collect::<Vec<f64>>();
+    let total = weights.iter().sum();
+    (weights, total)
+}
+
+/// The shared pattern matrix and the column of each word in play, if every one of them is a
+/// possible answer.
+struct Matrix {
+    matrix: Option<(&'static PatternMatrix, Vec<usize>)>,
+}
+
+impl Matrix {
+    fn for_words(words: &[&'static str]) -> Self {
+        let matrix = PatternMatrix::shared().and_then(|matrix| {
+            words
+                .iter()
+                .map(|word| matrix.answer_index(word))
+                .collect::<Option<Vec<usize>>>()
+                .map(|columns| (matrix, columns))
+        });
+        Matrix { matrix }
+    }
+}
+
+/// Buckets `words` by the feedback `candidate` would get against each of them, returning the size
+/// of the largest bucket and the entropy of the split given each word's weight. Feedback is read
+/// from the pattern matrix when it covers the candidate and the words.
+fn split(
+    candidate: &str,
+    words: &[&'static str],
+    matrix: &Matrix,
+    weights: &[f64],
+    total: f64,
+) -> (usize, f64) {
+    let row = matrix.matrix.as_ref().and_then(|(matrix, columns)| {
+        matrix
+            .guess_index(candidate)
+            .map(|guess| (matrix.row(guess), columns))
+    });
+    let feedback = |i: usize| match row {
+        Some((row, columns)) => u32::from(row[columns[i]]),
+        None => pattern(candidate, words[i]),
+    };
+
+    // Short words have few enough patterns to count them directly; longer ones are sorted.
+    let buckets = if candidate.len() <= 5 {
+        let mut buckets = vec![(0_usize, 0_f64); pattern_count(candidate.len())];
+        for (i, weight) in weights.iter().enumerate() {
+            let bucket = &mut buckets[feedback(i) as usize];
+            bucket.0 += 1;
+            bucket.1 += weight;
+        }
+        buckets.retain(|(count, _)| *count > 0);
+        buckets
+    } else {
+        let mut patterns = weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| (feedback(i), *weight))
+            .collect::<Vec<(u32, f64)>>();
+        patterns.sort_unstable_by_key(|(pattern, _)| *pattern);
+        patterns
+            .iter()
+            .group_by(|(pattern, _)| *pattern)
+            .into_iter()
+            .map(|(_, group)| {
+                group.fold((0_usize, 0_f64), |(count, weight), (_, w)| {
+                    (count + 1, weight + w)
+                })
+            })
+            .collect::<Vec<(usize, f64)>>()
+    };
+
+    let remaining = buckets.iter().map(|(count, _)| *count).max().unwrap_or(0);
+    let entropy = buckets
+        .iter()
+        .map(|(_, weight)| {
+            let p = weight / total;
+            -p * p.log2()
+        })
+        .sum::<f64>();
+    // A single bucket gives -0.0, which would be printed with its sign.
+    (remaining, entropy + 0.0)
+}
+
+fn sort(reduction: &mut [Suggestion], ranking: Ranking) {
+    match ranking {
+        Ranking::Entropy => reduction.par_sort_by(|a, b| {
+            b.entropy
+                .total_cmp(&a.entropy)
+                .then(a.remaining.cmp(&b.remaining))
+                .then(b.frequency.cmp(&a.frequency))
+                .then(b.score.cmp(&a.score))
+                .then(a.word.cmp(b.word))
+        }),
+        Ranking::WorstCase => reduction.par_sort_by(|a, b| {
+            a.remaining
+                .cmp(&b.remaining)
+                .then(b.entropy.total_cmp(&a.entropy))
+                .then(b.frequency.cmp(&a.frequency))
+                .then(b.score.cmp(&a.score))
+                .then(a.word.cmp(b.word))
+        }),
+    }
+}
+
+fn calculate_score(index: &DictionarySet, answers: &WordBits, word: &'static str) -> i64 {
     word.chars()
         .enumerate()
-        .map(|(i, c)| {
-            dictionary
-                .list_for_position(i)
-                .get(&c)
-                .map_or(0, |v| v.len())
-        })
+        .map(|(i, c)| index.at_position(i, c).count_and(answers))
         .sum::<usize>()
         .try_into()
         .unwrap()
 }
 
-fn pattern_from_word(word: &str) -> String {
-    let mut sorted_chars = Vec::from_iter(word.chars());
-    sorted_chars.sort();
-    sorted_chars.iter().collect()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::eliminate_words;
+    use crate::game::compare;
+
+    #[test]
+    fn contest_pool_keeps_only_answers() {
+        let word_list = WordList::from(vec!["crane", "xylyl"]);
+        let answers = AnswerPool::Contest.answers(&word_list);
+        assert_eq!(answers.get(), vec!["crane"]);
+        assert_eq!(AnswerPool::Full.answers(&word_list).word_count(), 2);
+
+        // Nothing would be left, so the list is kept as it is.
+        let custom = WordList::from(vec!["xylyl"]);
+        assert_eq!(AnswerPool::Contest.answers(&custom).get(), vec!["xylyl"]);
+    }
+
+    #[test]
+    fn ranks_by_entropy_or_worst_case() {
+        let words = vec![
+            "bluff", "fleck", "flyer", "cabin", "mulch", "lapse", "twist", "being", "loose",
+            "dealt", "tweed",
+        ];
+        let top = |ranking| {
+            let suggestions = suggest(
+                WordList::from(words.clone()),
+                false,
+                ranking,
+                AnswerPool::Full,
+            )
+            .unwrap();
+            assert_eq!(suggestions.len(), words.len());
+            (suggestions[0].word, suggestions[0].remaining)
+        };
+        // "fleck" splits the words best on average but can leave three; "lapse" never leaves more
+        // than two.
+        assert_eq!(top(Ranking::Entropy), ("fleck", 3));
+        assert_eq!(top(Ranking::WorstCase), ("lapse", 2));
+    }
+
+    #[test]
+    fn a_guess_that_tells_nothing_has_no_entropy() {
+        let suggestions = suggest(
+            WordList::from(vec!["crane", "crate"]),
+            true,
+            Ranking::Entropy,
+            AnswerPool::Full,
+        )
+        .unwrap();
+        let fuzzy = suggestions.iter().find(|s| s.word == "fuzzy").unwrap();
+        assert_eq!(fuzzy.remaining, 2);
+        assert!(fuzzy.entropy == 0.0 && fuzzy.entropy.is_sign_positive());
+    }
+
+    #[test]
+    fn multi_finishes_a_board_with_one_word_left() {
+        let boards = vec![
+            WordList::from(vec![]),
+            WordList::from(vec!["crane", "crate", "grace"]),
+            WordList::from(vec!["slate"]),
+        ];
+        let suggestions =
+            suggest_multi(boards, &[], true, Ranking::Entropy, AnswerPool::Contest).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].word, "slate");
+
+        let none = suggest_multi(
+            vec![WordList::from(vec![])],
+            &[],
+            true,
+            Ranking::Entropy,
+            AnswerPool::Full,
+        );
+        assert_eq!(none.unwrap_err(), SuggestError::NoWordsLeft);
+    }
+
+    #[test]
+    fn multi_rates_guesses_on_every_board() {
+        let boards = vec![
+            WordList::from(vec!["crane", "crate"]),
+            WordList::from(vec!["slate", "plate"]),
+        ];
+        let suggestions =
+            suggest_multi(boards, &[], true, Ranking::WorstCase, AnswerPool::Full).unwrap();
+        // A guess holding an n or a t and an s or a p leaves one word on each board.
+        assert_eq!(suggestions[0].remaining, 2);
+        assert!(suggestions[0].entropy >= 2.0 - 1e-9);
+    }
+
+    #[test]
+    fn multi_keeps_to_hard_mode_on_every_board() {
+        let letters = [compare("trace", "crane"), compare("trace", "slate")];
+        let word_lists = letters
+            .iter()
+            .map(|row| eliminate_words(WordList::new(), row.clone()))
+            .collect::<Vec<WordList>>();
+        let revealed = letters
+            .iter()
+            .map(|row| Constraints::new(5).apply(row))
+            .collect::<Vec<Constraints>>();
+
+        let suggestions = suggest_multi(
+            word_lists,
+            &revealed,
+            false,
+            Ranking::Entropy,
+            AnswerPool::Contest,
+        )
+        .unwrap();
+        assert!(!suggestions.is_empty());
+        for suggestion in suggestions {
+            for known in &revealed {
+                assert!(known.check_hard_mode(suggestion.word).is_ok());
+            }
+        }
+    }
+}
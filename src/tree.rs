@@ -0,0 +1,247 @@
+//! Precomputed decision trees that fix the guess for every possible feedback.
+
+use std::collections::BTreeMap;
+
+use crate::constraints::Constraints;
+use crate::feedback::{format_marks, Notation};
+use crate::game::{compare, pattern, LetterResult};
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A precomputed strategy: the guess to make and, for every feedback that guess can get, the node
+/// to continue from. Feedback is keyed by its `G`/`Y`/`.` marks; solving feedback has no child.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Node {
+    /// The guess to make at this point.
+    pub guess: String,
+    /// Where to go next after each feedback the guess can get.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub children: BTreeMap<String, Node>,
+}
+
+/// A strategy covering every answer, as written by the `tree` command.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecisionTree {
+    /// Whether every guess in the tree is allowed in hard mode.
+    pub hard: bool,
+    /// The guess limit the tree was built for.
+    pub max_guesses: usize,
+    /// Guesses needed to solve every answer, counting a failed answer as one more than the limit.
+    pub total_guesses: usize,
+    /// Number of answers the tree covers.
+    pub answers: usize,
+    /// Answers not solved within the limit.
+    pub failures: usize,
+    /// The opening guess and everything after it.
+    pub root: Node,
+}
+
+struct Plan {
+    node: Node,
+    total: usize,
+    failures: usize,
+}
+
+impl DecisionTree {
+    /// Builds a strategy that solves every word in `answers` using words from `guesses`, trying to
+    /// minimize the expected number of guesses without going over `max_guesses`.
+    ///
+    /// An exhaustive search is out of reach, so at every node only the `width` guesses that split
+    /// the remaining answers best (by entropy) are explored in full, and the one whose subtree
+    /// solves the most answers in the fewest total guesses is kept. A width of one is a plain
+    /// greedy strategy; every extra unit multiplies the work at each level.
+    pub fn build(
+        answers: &[&'static str],
+        guesses: &[&'static str],
+        hard: bool,
+        max_guesses: usize,
+        width: usize,
+    ) -> Option<Self> {
+        let length = answers.first()?.len();
+        let guesses = guesses
+            .iter()
+            .filter(|g| g.len() == length)
+            .copied()
+            .collect::<Vec<&'static str>>();
+        let builder = Builder {
+            guesses: &guesses,
+            hard,
+            max_guesses,
+            width: width.max(1),
+        };
+
+        builder
+            .build(answers, &Constraints::new(length), 0)
+            .map(|plan| DecisionTree {
+                hard,
+                max_guesses,
+                total_guesses: plan.total,
+                answers: answers.len(),
+                failures: plan.failures,
+                root: plan.node,
+            })
+    }
+
+    /// Average guesses per answer.
+    pub fn mean(&self) -> f64 {
+        self.total_guesses as f64 / self.answers.max(1) as f64
+    }
+
+    /// Follows the tree along the guesses made so far. Returns `None` once the game has left the
+    /// tree, either because a different guess was played or the feedback was never expected.
+    pub fn next_guess(&self, rows: &[Vec<LetterResult>]) -> Option<&str> {
+        let mut node = &self.root;
+        for row in rows {
+            let guess: String = row.iter().map(|letter| letter.to_char()).collect();
+            if guess != node.guess {
+                return None;
+            }
+            node = node.children.get(&format_marks(row, Notation::Letters))?;
+        }
+        Some(&node.guess)
+    }
+}
+
+struct Builder<'a> {
+    guesses: &'a [&'static str],
+    hard: bool,
+    max_guesses: usize,
+    width: usize,
+}
+
+impl<'a> Builder<'a> {
+    /// Plans how to solve `answers` with `depth` guesses already made. Returns `None` when there
+    /// are no guesses left.
+    fn build(&self, answers: &[&'static str], known: &Constraints, depth: usize) -> Option<Plan> {
+        if depth >= self.max_guesses {
+            return None;
+        }
+
+        if answers.len() == 1 {
+            return Some(Plan {
+                node: Node {
+                    guess: answers[0].to_string(),
+                    children: BTreeMap::new(),
+                },
+                total: 1,
+                failures: 0,
+            });
+        }
+
+        self.candidates(answers, known)
+            .into_iter()
+            .map(|guess| self.plan_for(guess, answers, known, depth))
+            .min_by_key(|plan| (plan.failures, plan.total))
+    }
+
+    fn plan_for(
+        &self,
+        guess: &'static str,
+        answers: &[&'static str],
+        known: &Constraints,
+        depth: usize,
+    ) -> Plan {
+        let mut plan = Plan {
+            node: Node {
+                guess: guess.to_string(),
+                children: BTreeMap::new(),
+            },
+            total: answers.len(),
+            failures: 0,
+        };
+
+        let buckets = answers
+            .iter()
+            .copied()
+            .into_group_map_by(|answer| pattern(guess, answer));
+        for (_, bucket) in buckets {
+            let letters = compare(guess, bucket[0]);
+            if letters.iter().all(|l| matches!(l, LetterResult::Exact(_))) {
+                continue;
+            }
+
+            let known = known.clone().apply(&letters);
+            match self.build(&bucket, &known, depth + 1) {
+                Some(child) => {
+                    plan.total += child.total;
+                    plan.failures += child.failures;
+                    plan.node
+                        .children
+                        .insert(format_marks(&letters, Notation::Letters), child.node);
+                }
+                None => {
+                    plan.total += bucket.len();
+                    plan.failures += bucket.len();
+                }
+            }
+        }
+        plan
+    }
+
+    /// The `width` guesses that split `answers` best. Guesses that could be the answer win ties,
+    /// and when only two answers are left guessing one of them is always best.
+    fn candidates(&self, answers: &[&'static str], known: &Constraints) -> Vec<&'static str> {
+        if answers.len() <= 2 {
+            return answers.to_vec();
+        }
+
+        let mut ranked = self
+            .guesses
+            .par_iter()
+            .filter(|guess| !self.hard || known.check_hard_mode(guess).is_ok())
+            .map(|guess| {
+                let mut patterns = answers
+                    .iter()
+                    .map(|answer| pattern(guess, answer))
+                    .collect::<Vec<u32>>();
+                patterns.sort_unstable();
+                // Lower is better: minimizing the sum of n*log(n) over the buckets maximizes
+                // entropy over a uniform answer pool.
+                let spread: f64 = patterns
+                    .iter()
+                    .dedup_with_count()
+                    .map(|(count, _)| count as f64 * (count as f64).log2())
+                    .sum();
+                (*guess, spread, !answers.contains(guess))
+            })
+            .collect::<Vec<(&'static str, f64, bool)>>();
+
+        ranked.par_sort_by(|a, b| a.1.total_cmp(&b.1).then(a.2.cmp(&b.2)).then(a.0.cmp(b.0)));
+        ranked
+            .into_iter()
+            .take(self.width)
+            .map(|(guess, _, _)| guess)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWERS: [&str; 6] = ["crane", "crate", "trace", "react", "caret", "cater"];
+
+    #[test]
+    fn solves_every_answer_and_follows_play() {
+        let tree = DecisionTree::build(&ANSWERS, &ANSWERS, true, 6, 2).unwrap();
+        assert_eq!(tree.failures, 0);
+        assert_eq!(tree.answers, ANSWERS.len());
+
+        for answer in ANSWERS.iter() {
+            let mut rows = vec![];
+            loop {
+                let guess = tree.next_guess(&rows).unwrap().to_string();
+                let letters = compare(&guess, answer);
+                if guess == *answer {
+                    break;
+                }
+                rows.push(letters);
+                assert!(rows.len() < 6);
+            }
+        }
+
+        let off_tree = vec![compare("zzzzz", "crane")];
+        assert_eq!(tree.next_guess(&off_tree), None);
+    }
+}
@@ -1,79 +1,140 @@
-use std::{collections::HashSet, iter::FromIterator};
+//! The accepted guesses and the puzzle answers, built in or loaded from files.
 
+use crate::game::{MAX_LENGTH, MIN_LENGTH};
+use std::{
+    collections::HashSet,
+    io::{Error, ErrorKind},
+    iter::FromIterator,
+    sync::OnceLock,
+};
+
+static GUESSES: OnceLock<Vec<String>> = OnceLock::new();
+static ANSWERS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// A set of words of the same length, either one of the two global lists or some part of them.
 #[derive(Clone, Debug)]
 pub struct WordList {
     word_list: HashSet<&'static str>,
 }
 
-#[allow(dead_code)]
 impl WordList {
+    /// Every word accepted as a guess.
     pub fn new() -> Self {
-        make_word_list().into()
+        guesses()
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .into()
     }
 
+    /// Every word that can be a puzzle answer.
     pub fn contest() -> Self {
-        make_contest_list().into()
+        answers()
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .into()
     }
 
+    /// The secret word of puzzle `day`.
     pub fn get_word_for_day(day: usize) -> Option<&'static str> {
-        make_contest_list().get(day).map(|x| *x)
+        answers().get(day).map(String::as_str)
     }
 
-    pub fn word_count(&self) -> usize {
-        self.word_list.len()
+    /// A hash of the guess and answer lists in use, the answers in puzzle order. It is stable
+    /// between builds, so files kept for one pair of lists can be told apart from another's.
+    pub fn lists_hash() -> u64 {
+        hash_lists(
+            guesses().iter().map(String::as_str),
+            answers().iter().map(String::as_str),
+        )
     }
 
-    pub fn get(&self) -> Vec<&'static str> {
-        self.word_list.iter().map(|x| *x).collect()
+    /// Replaces the built-in guess list, answer list or both; the order of the answers determines
+    /// each day's puzzle. Answers missing from the guesses are added to them, so every answer can
+    /// be guessed. Must be called before either list is first used.
+    pub fn set_lists(
+        guesses: Option<Vec<String>>,
+        answers: Option<Vec<String>>,
+    ) -> Result<(), Error> {
+        let (guesses, answers) = merge_lists(
+            guesses.unwrap_or_else(|| make_word_list().into_iter().map(String::from).collect()),
+            answers.unwrap_or_else(|| make_contest_list().into_iter().map(String::from).collect()),
+        )?;
+        let loaded = || Error::new(ErrorKind::AlreadyExists, "word lists already loaded");
+        GUESSES.set(guesses).map_err(|_| loaded())?;
+        ANSWERS.set(answers).map_err(|_| loaded())
     }
 
-    pub fn remove_letter(&self, letter: char) -> WordList {
-        WordList {
-            word_list: self
-                .word_list
-                .iter()
-                .filter(|w| !w.contains(letter))
-                .map(|w| *w)
-                .collect(),
+    /// Parses a word list with one word per line. Blank lines are skipped and words are lowercased.
+    /// Every word must have the same length as the first one.
+    pub fn parse(contents: &str) -> Result<Vec<String>, Error> {
+        let words = contents
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.trim().to_ascii_lowercase()))
+            .filter(|(_, word)| !word.is_empty())
+            .collect::<Vec<(usize, String)>>();
+        let length = words.first().map_or(0, |(_, word)| word.len());
+        if !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "words must have between {} and {} letters",
+                    MIN_LENGTH, MAX_LENGTH
+                ),
+            ));
         }
+
+        words
+            .into_iter()
+            .map(|(i, word)| {
+                if word.len() == length && word.chars().all(|c| c.is_ascii_lowercase()) {
+                    Ok(word)
+                } else {
+                    Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("line {}: '{}' is not a {}-letter word", i + 1, word, length),
+                    ))
+                }
+            })
+            .collect()
     }
 
-    pub fn ensure_letter(&self, letter: char) -> WordList {
-        WordList {
-            word_list: self
-                .word_list
-                .iter()
-                .filter(|w| w.contains(letter))
-                .map(|w| *w)
-                .collect(),
-        }
+    /// Number of words in the list.
+    pub fn word_count(&self) -> usize {
+        self.word_list.len()
     }
 
-    pub fn whittle(&self, letter: char) -> WordList {
-        let lists: (Vec<&'static str>, Vec<&'static str>) =
-            self.word_list.iter().partition(|w| w.contains(letter));
-        if lists.0.len() > lists.1.len() {
-            lists.0.into()
-        } else {
-            lists.1.into()
-        }
+    /// Whether `word` is in the list.
+    pub fn contains(&self, word: &str) -> bool {
+        self.word_list.contains(word)
     }
 
-    pub fn intersect(&self, list: Vec<&'static str>) -> WordList {
-        let other = HashSet::from_iter(list);
-        WordList {
-            word_list: self.word_list.intersection(&other).map(|x| *x).collect(),
-        }
+    /// Looks up `word`, returning the list's own copy of it.
+    pub fn find(&self, word: &str) -> Option<&'static str> {
+        self.word_list.get(word).copied()
+    }
+
+    /// The words, in no particular order.
+    pub fn get(&self) -> Vec<&'static str> {
+        self.word_list.iter().copied().collect()
     }
 
-    pub fn subtract(&self, list: Vec<&'static str>) -> WordList {
-        let other = HashSet::from_iter(list);
+    /// The words for which `keep` is true.
+    pub fn retain<F: Fn(&str) -> bool>(&self, keep: F) -> WordList {
         WordList {
-            word_list: self.word_list.difference(&other).map(|x| *x).collect(),
+            word_list: self.word_list.iter().filter(|w| keep(w)).copied().collect(),
         }
     }
 }
 
+impl Default for WordList {
+    fn default() -> Self {
+        WordList::new()
+    }
+}
+
 impl From<Vec<&'static str>> for WordList {
     fn from(word_list: Vec<&'static str>) -> Self {
         WordList {
@@ -82,6 +143,51 @@ impl From<Vec<&'static str>> for WordList {
     }
 }
 
+/// Checks both lists hold words of the same length and adds the answers to the guesses.
+fn merge_lists(
+    mut guesses: Vec<String>,
+    answers: Vec<String>,
+) -> Result<(Vec<String>, Vec<String>), Error> {
+    let length = |words: &[String]| words.first().map_or(0, |w| w.len());
+    if length(&guesses) != length(&answers) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "the guesses have {} letters but the answers have {}",
+                length(&guesses),
+                length(&answers)
+            ),
+        ));
+    }
+
+    let known = guesses.iter().cloned().collect::<HashSet<String>>();
+    guesses.extend(answers.iter().filter(|a| !known.contains(*a)).cloned());
+    Ok((guesses, answers))
+}
+
+/// FNV-1a over both lists, which unlike `DefaultHasher` is stable between builds.
+pub(crate) fn hash_lists<'a>(
+    guesses: impl IntoIterator<Item = &'a str>,
+    answers: impl IntoIterator<Item = &'a str>,
+) -> u64 {
+    guesses
+        .into_iter()
+        .chain(["\n"])
+        .chain(answers)
+        .flat_map(|w| w.bytes().chain([b'\n']))
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+fn guesses() -> &'static [String] {
+    GUESSES.get_or_init(|| make_word_list().into_iter().map(String::from).collect())
+}
+
+fn answers() -> &'static [String] {
+    ANSWERS.get_or_init(|| make_contest_list().into_iter().map(String::from).collect())
+}
+
 fn make_word_list() -> Vec<&'static str> {
     vec![
         "aahed", "aalii", "aargh", "aarti", "abaca", "abaci", "abacs", "abaft", "abaka", "abamp",
@@ -1620,3 +1726,40 @@ fn make_contest_list() -> Vec<&'static str> {
         "unlit", "vomit", "fanny", "fetus", "butch", "stalk", "flack", "widow", "augur",
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lowercases_and_skips_blank_lines() {
+        let words = WordList::parse("Crane\n\n  slate \r\nGEESE\n").unwrap();
+        assert_eq!(words, vec!["crane", "slate", "geese"]);
+    }
+
+    #[test]
+    fn parse_rejects_bad_words() {
+        let error = WordList::parse("crane\nslates\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: 'slates' is not a 5-letter word");
+        let error = WordList::parse("crane\n\ncr4ne\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 3: 'cr4ne' is not a 5-letter word");
+        assert!(WordList::parse("").is_err());
+        assert!(WordList::parse("cat\n").is_err());
+        assert!(WordList::parse("abcdefghijkl\n").is_err());
+    }
+
+    #[test]
+    fn answers_are_added_to_the_guesses() {
+        let words = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<String>>();
+        let (guesses, answers) =
+            merge_lists(words(&["crane", "slate"]), words(&["geese", "crane"])).unwrap();
+        assert_eq!(guesses, words(&["crane", "slate", "geese"]));
+        assert_eq!(answers, words(&["geese", "crane"]));
+
+        let error = merge_lists(words(&["cranes"]), words(&["crane"])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the guesses have 6 letters but the answers have 5"
+        );
+    }
+}